@@ -0,0 +1,259 @@
+//! 論理式をハッシュコンシングして格納するアリーナを定義するモジュールです．
+//! 同じ論理式は1つのノードとして格納されて同じ[FormulaId]で示されるため，部分式は共有され，
+//! 論理式の比較とハッシュ値の計算は木の大きさによらず定数時間で行えます．
+//! 証明探索と古典論理上の検証はこのアリーナの上で行い，入出力では[Logic]と相互に変換します．
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Index;
+
+use super::logic::*;
+
+/// アリーナに格納された論理式を示す番号です．同じアリーナの中では，論理式と番号が一対一対応します．
+/// 異なるアリーナの番号を比べても意味はありません．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FormulaId(usize);
+
+/// アリーナに格納された論理式のノードを示す列挙子です．[Logic]と同じ形で，部分式を番号で持ちます．
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Formula {
+  Base(String),
+  Cont,
+  Not(FormulaId),
+  And(FormulaId, FormulaId),
+  Or(FormulaId, FormulaId),
+  To(FormulaId, FormulaId),
+  Iff(FormulaId, FormulaId),
+}
+
+impl Formula {
+  /// 根の演算子が直接とる部分式を列挙します．
+  pub fn children(&self) -> Vec<FormulaId> {
+    match *self {
+      Self::Base(_) | Self::Cont => vec![],
+      Self::Not(id) => vec![id],
+      Self::And(left, right) | Self::Or(left, right) | Self::To(left, right) | Self::Iff(left, right) => {
+        vec![left, right]
+      }
+    }
+  }
+}
+
+/// 論理式のアリーナです．格納した論理式は取り除かれず，番号は格納した順に振られます．
+#[derive(Debug, Clone, Default)]
+pub struct Arena {
+  /// 格納した論理式のノードです．番号を添字とします．
+  formulas: Vec<Formula>,
+
+  /// ノードから番号を引く表です．
+  ids: HashMap<Formula, FormulaId>,
+}
+
+impl Arena {
+  /// 空のアリーナを生成します．
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 格納した論理式の数です．共有された部分式は1つと数えます．
+  pub fn len(&self) -> usize {
+    self.formulas.len()
+  }
+
+  /// 論理式を1つも格納していないかを返します．
+  pub fn is_empty(&self) -> bool {
+    self.formulas.is_empty()
+  }
+
+  /// ノードを格納し，その番号を返します．同じノードが既にあれば，その番号を返します．
+  pub fn intern(&mut self, formula: Formula) -> FormulaId {
+    if let Some(&id) = self.ids.get(&formula) {
+      return id;
+    }
+    let id = FormulaId(self.formulas.len());
+    self.formulas.push(formula.clone());
+    self.ids.insert(formula, id);
+    id
+  }
+
+  /// 論理式`logic`を格納し，その番号を返します．部分式も格納され，既にあるものは共有されます．
+  pub fn insert(&mut self, logic: &Logic) -> FormulaId {
+    let mut stack = vec![(logic, false)];
+    let mut ids = vec![];
+    while let Some((logic, visited)) = stack.pop() {
+      let children = logic.children();
+      if !visited {
+        stack.push((logic, true));
+        stack.extend(children.into_iter().rev().map(|child| (child, false)));
+        continue;
+      }
+
+      let mut children = ids.split_off(ids.len() - children.len()).into_iter();
+      let mut child = || children.next().unwrap();
+      let formula = match logic {
+        Logic::Base(name) => Formula::Base(name.clone()),
+        Logic::Cont => Formula::Cont,
+        Logic::Not(_) => Formula::Not(child()),
+        Logic::And(_, _) => Formula::And(child(), child()),
+        Logic::Or(_, _) => Formula::Or(child(), child()),
+        Logic::To(_, _) => Formula::To(child(), child()),
+        Logic::Iff(_, _) => Formula::Iff(child(), child()),
+      };
+      ids.push(self.intern(formula));
+    }
+    ids.pop().unwrap()
+  }
+
+  /// 番号`id`の論理式を[Logic]の木に変換します．共有された部分式は複製されます．
+  pub fn logic(&self, id: FormulaId) -> Logic {
+    let mut stack = vec![(id, false)];
+    let mut logics: Vec<Logic> = vec![];
+    while let Some((id, visited)) = stack.pop() {
+      let children = self[id].children();
+      if !visited {
+        stack.push((id, true));
+        stack.extend(children.into_iter().rev().map(|child| (child, false)));
+        continue;
+      }
+
+      let mut children = logics.split_off(logics.len() - children.len()).into_iter().map(Box::new);
+      let mut child = || children.next().unwrap();
+      logics.push(match self[id] {
+        Formula::Base(ref name) => Logic::Base(name.clone()),
+        Formula::Cont => Logic::Cont,
+        Formula::Not(_) => Logic::Not(child()),
+        Formula::And(_, _) => Logic::And(child(), child()),
+        Formula::Or(_, _) => Logic::Or(child(), child()),
+        Formula::To(_, _) => Logic::To(child(), child()),
+        Formula::Iff(_, _) => Logic::Iff(child(), child()),
+      });
+    }
+    logics.pop().unwrap()
+  }
+
+  /// 番号`id`の論理式にあるすべての原子式の名前を，名前順に列挙します．
+  pub fn bases(&self, id: FormulaId) -> BTreeSet<String> {
+    let mut bases = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut stack = vec![id];
+    while let Some(id) = stack.pop() {
+      if !visited.insert(id) {
+        continue;
+      }
+      match self[id] {
+        Formula::Base(ref name) => {
+          bases.insert(name.clone());
+        }
+        ref formula => stack.extend(formula.children()),
+      }
+    }
+    bases
+  }
+
+  /// 番号`id`の論理式に`map`の真偽値を代入し，評価できる限り評価します．
+  /// 真偽が決まればその値を，真偽値が与えられていない原子式のために決まらなければ[None]を返します．
+  /// 共有された部分式は一度だけ評価し，評価の途中で論理式を格納することはありません．
+  pub fn eval_part(&self, id: FormulaId, map: &BTreeMap<String, bool>) -> Option<bool> {
+    let mut values: HashMap<FormulaId, Option<bool>> = HashMap::new();
+    let mut stack = vec![(id, false)];
+    while let Some((id, visited)) = stack.pop() {
+      if values.contains_key(&id) {
+        continue;
+      }
+      if !visited {
+        stack.push((id, true));
+        stack.extend(self[id].children().into_iter().rev().map(|child| (child, false)));
+        continue;
+      }
+
+      let value = |id| values[&id];
+      let value = match self[id] {
+        Formula::Base(ref name) => map.get(name).copied(),
+        Formula::Cont => Some(false),
+        Formula::Not(logic) => value(logic).map(|value| !value),
+        Formula::And(left, right) => match (value(left), value(right)) {
+          (Some(false), _) | (_, Some(false)) => Some(false),
+          (Some(true), Some(true)) => Some(true),
+          _ => None,
+        },
+        Formula::Or(left, right) => match (value(left), value(right)) {
+          (Some(true), _) | (_, Some(true)) => Some(true),
+          (Some(false), Some(false)) => Some(false),
+          _ => None,
+        },
+        Formula::To(left, right) => match (value(left), value(right)) {
+          (Some(false), _) | (_, Some(true)) => Some(true),
+          (Some(true), Some(false)) => Some(false),
+          _ => None,
+        },
+        Formula::Iff(left, right) => match (value(left), value(right)) {
+          (Some(left), Some(right)) => Some(left == right),
+          _ => None,
+        },
+      };
+      values.insert(id, value);
+    }
+    values[&id]
+  }
+
+  /// 番号`id`の論理式の真偽値を評価します．論理式にあるすべての原子式の真偽値が`map`に与えられている必要があり，
+  /// 与えられていない原子式があれば偽を返します．
+  pub fn eval(&self, id: FormulaId, map: &BTreeMap<String, bool>) -> bool {
+    self.eval_part(id, map) == Some(true)
+  }
+}
+
+impl Index<FormulaId> for Arena {
+  type Output = Formula;
+  fn index(&self, id: FormulaId) -> &Formula {
+    &self.formulas[id.0]
+  }
+}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+
+  #[test]
+  fn test_intern() {
+    let mut arena = Arena::new();
+    let logic = Logic::new("(A and B) or (A and B) to A").unwrap();
+    let id = arena.insert(&logic);
+    assert_eq!(arena.len(), 5);
+    assert_eq!(arena.logic(id), logic);
+
+    let and = arena.insert(&Logic::new("A and B").unwrap());
+    assert_eq!(arena[and], Formula::And(FormulaId(0), FormulaId(1)));
+    match arena[id] {
+      Formula::To(left, _) => assert_eq!(arena[left], Formula::Or(and, and)),
+      _ => panic!(),
+    }
+    assert_eq!(arena.insert(&logic), id);
+    assert_eq!(arena.len(), 5);
+  }
+
+  #[test]
+  fn test_eval_part() {
+    let mut arena = Arena::new();
+    let id = arena.insert(&Logic::new("(A to B) and (A or C)").unwrap());
+    let len = arena.len();
+    let map: BTreeMap<_, _> = vec![("A".to_string(), true)].into_iter().collect();
+    assert_eq!(arena.eval_part(id, &map), None);
+
+    let map: BTreeMap<_, _> = vec![("A".to_string(), false), ("C".to_string(), false)]
+      .into_iter()
+      .collect();
+    assert_eq!(arena.eval_part(id, &map), Some(false));
+    assert!(!arena.eval(id, &map));
+
+    let map: BTreeMap<_, _> = vec![("A".to_string(), false), ("C".to_string(), true)]
+      .into_iter()
+      .collect();
+    assert_eq!(arena.eval_part(id, &map), Some(true));
+    assert_eq!(arena.len(), len);
+
+    let bases: Vec<_> = arena.bases(id).into_iter().collect();
+    assert_eq!(bases, ["A", "B", "C"]);
+  }
+}
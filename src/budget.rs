@@ -0,0 +1,119 @@
+//! 探索に用いる資源の制限と，探索全体で共有するその消費量を実装するモジュールです．
+//! 自然演繹の証明探索，シーケント計算の導出の探索，クリプキモデルの探索で共有します．
+
+use std::cell::Cell;
+use std::fmt::Display;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 証明探索に用いる資源の制限です．[None]の項目は制限しません．既定ではどれも制限しません．
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+  /// 探索の深さ，すなわち入れ子になった問題の数の上限です．
+  pub max_depth: Option<usize>,
+
+  /// 探索する問題の数の上限です．
+  pub max_nodes: Option<usize>,
+
+  /// 探索を始めてからの経過時間の上限です．
+  pub timeout: Option<Duration>,
+
+  /// 取り消しのフラグです．他のスレッドから`true`にすると，探索を打ち切ります．
+  pub cancel: Option<Arc<AtomicBool>>,
+}
+
+/// 探索全体で共有する資源の制限と，その消費量を示す構造です．
+#[derive(Debug)]
+pub struct Budget {
+  /// 資源の制限です．
+  limits: Limits,
+
+  /// 探索を始めた時刻です．
+  start: Instant,
+
+  /// これまでに探索した問題の数です．
+  nodes: Cell<usize>,
+
+  /// 今の探索の深さです．
+  depth: Cell<usize>,
+}
+
+impl Budget {
+  /// 探索を始める時刻を今として，新しい資源の消費量を生成します．
+  pub fn new(limits: Limits) -> Self {
+    Self {
+      limits,
+      start: Instant::now(),
+      nodes: Cell::new(0),
+      depth: Cell::new(0),
+    }
+  }
+
+  /// 問題を1つ探索することを記録します．制限に達していればエラーを返します．
+  /// 返された値が破棄されるまで，探索の深さを1つ深くします．
+  pub fn enter(self: &Rc<Self>) -> Result<Depth, Resource> {
+    let limits = &self.limits;
+    if let Some(cancel) = &limits.cancel {
+      if cancel.load(Ordering::Relaxed) {
+        return Err(Resource::Cancelled);
+      }
+    }
+    if let Some(timeout) = limits.timeout {
+      if self.start.elapsed() > timeout {
+        return Err(Resource::Timeout(timeout));
+      }
+    }
+    if let Some(max) = limits.max_nodes {
+      if self.nodes.get() >= max {
+        return Err(Resource::Nodes(max));
+      }
+    }
+    if let Some(max) = limits.max_depth {
+      if self.depth.get() >= max {
+        return Err(Resource::Depth(max));
+      }
+    }
+
+    self.nodes.set(self.nodes.get() + 1);
+    self.depth.set(self.depth.get() + 1);
+    Ok(Depth(self.clone()))
+  }
+}
+
+/// 探索の深さを1つ深くしている間保持される値です．破棄されると深さを戻します．
+pub struct Depth(Rc<Budget>);
+
+impl Drop for Depth {
+  fn drop(&mut self) {
+    self.0.depth.set(self.0.depth.get() - 1);
+  }
+}
+
+/// 探索を打ち切る原因となった資源の制限を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+  /// 探索の深さの上限です．
+  Depth(usize),
+
+  /// 探索する問題の数の上限です．
+  Nodes(usize),
+
+  /// 経過時間の上限です．
+  Timeout(Duration),
+
+  /// 取り消しのフラグが立てられました．
+  Cancelled,
+}
+
+impl Display for Resource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Depth(max) => write!(f, "exceeded the maximum depth of {}", max),
+      Self::Nodes(max) => write!(f, "explored more than {} nodes", max),
+      Self::Timeout(timeout) => write!(f, "timed out after {:?}", timeout),
+      Self::Cancelled => write!(f, "was cancelled"),
+    }
+  }
+}
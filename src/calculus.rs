@@ -0,0 +1,740 @@
+//! 推件のシーケント計算による導出を探索し，証明図として出力するモジュールです．
+//! 直観主義論理ではLJを，古典論理ではLKを用います．いずれも構造規則を論理規則に吸収した体系（G3i，G3c）で，
+//! 前件と後件は重複のない論理式の列として扱い，公理`Γ, A ⊢ A, Δ`は任意の論理式`A`について認めます．
+//!
+//! LJの探索では，可逆な規則を先に適用し，可逆でない規則（`∨R`，`→L`，`¬L`，`↔L`）を試す際には，
+//! 同じ枝に同じ推件が現れたらその枝を諦めることで停止性を保証しています．
+//! LKの規則はすべて可逆なので，論理式を分解するだけで導出が得られます．
+
+use std::fmt::Display;
+use std::rc::Rc;
+use std::vec::IntoIter;
+
+use super::{budget::*, kripke::Kripke, logic::*, TeX};
+
+/// シーケント計算の推論規則を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SequentRule {
+  /// 公理（Ax）です．前件と後件に同じ論理式があります．
+  Axiom,
+
+  /// 矛盾の左規則（⊥L）です．前件に矛盾があります．
+  ContLeft,
+
+  /// 否定の左規則（¬L）です．
+  NotLeft,
+
+  /// 否定の右規則（¬R）です．
+  NotRight,
+
+  /// 論理積の左規則（∧L）です．
+  AndLeft,
+
+  /// 論理積の右規則（∧R）です．
+  AndRight,
+
+  /// 論理和の左規則（∨L）です．
+  OrLeft,
+
+  /// 論理和の右規則（∨R）です．
+  OrRight,
+
+  /// 論理包含の左規則（→L）です．
+  ToLeft,
+
+  /// 論理包含の右規則（→R）です．
+  ToRight,
+
+  /// 同値の左規則（↔L）です．
+  IffLeft,
+
+  /// 同値の右規則（↔R）です．
+  IffRight,
+}
+
+impl TeX for SequentRule {
+  fn tex(&self) -> String {
+    match self {
+      Self::Axiom => "\\mathrm{Ax}",
+      Self::ContLeft => "\\perp L",
+      Self::NotLeft => "\\lnot L",
+      Self::NotRight => "\\lnot R",
+      Self::AndLeft => "\\land L",
+      Self::AndRight => "\\land R",
+      Self::OrLeft => "\\lor L",
+      Self::OrRight => "\\lor R",
+      Self::ToLeft => "\\to L",
+      Self::ToRight => "\\to R",
+      Self::IffLeft => "\\leftrightarrow L",
+      Self::IffRight => "\\leftrightarrow R",
+    }
+    .to_string()
+  }
+}
+
+impl Display for SequentRule {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let name = match self {
+      Self::Axiom => "Ax",
+      Self::ContLeft => "⊥L",
+      Self::NotLeft => "¬L",
+      Self::NotRight => "¬R",
+      Self::AndLeft => "∧L",
+      Self::AndRight => "∧R",
+      Self::OrLeft => "∨L",
+      Self::OrRight => "∨R",
+      Self::ToLeft => "→L",
+      Self::ToRight => "→R",
+      Self::IffLeft => "↔L",
+      Self::IffRight => "↔R",
+    };
+    write!(f, "{}", name)
+  }
+}
+
+/// シーケント計算の導出です．木構造のノードで，証明図の横線と一対一対応します．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Derivation {
+  /// 導かれる推件の前件です．
+  antecedent: Vec<Logic>,
+
+  /// 導かれる推件の後件です．LJでは常に1つです．
+  succedent: Vec<Logic>,
+
+  /// 用いた推論規則です．
+  rule: SequentRule,
+
+  /// 前提となる導出です．
+  premises: Vec<Derivation>,
+}
+
+/// 論理式の列に，含まれていなければ`logic`を加えた列を返します．
+fn with(logics: &[Logic], logic: &Logic) -> Vec<Logic> {
+  let mut logics = logics.to_vec();
+  if !logics.contains(logic) {
+    logics.push(logic.clone());
+  }
+  logics
+}
+
+/// 論理式の列から，`i`番目の論理式を除いた列を返します．
+fn without(logics: &[Logic], i: usize) -> Vec<Logic> {
+  let mut logics = logics.to_vec();
+  logics.remove(i);
+  logics
+}
+
+/// 論理式の列を，順序を無視して比較します．
+fn same(left: &[Logic], right: &[Logic]) -> bool {
+  left.len() == right.len() && left.iter().all(|logic| right.contains(logic))
+}
+
+/// 論理式が原子式でも矛盾でもなく，規則で分解できるかを返します．
+fn is_compound(logic: &Logic) -> bool {
+  !matches!(logic, Logic::Base(_) | Logic::Cont)
+}
+
+/// 推件です．前件と後件の論理式の列の組です．
+type Goal = (Vec<Logic>, Vec<Logic>);
+
+/// 推件の導出の探索で次に行うことを示す列挙子です．
+enum Next {
+  /// 推件の導出を探索します．
+  Search(Goal),
+
+  /// 直前に探索した推件の導出を，スタックの上の探索に返します．
+  Return(Option<Derivation>),
+}
+
+/// 部分の探索の結果を待っている探索を示す列挙子です．
+enum Frame {
+  /// 探索の深さを1つ深くしています．結果を受け取ると深さを戻し，そのまま返します．
+  Leave(Depth),
+
+  /// 推件`goal`の前提`rest`を順に探索しています．すべて導出できれば，それらを`premises`に続けて前提とし，
+  /// 推件を規則`rule`で導出します．
+  Premises {
+    goal: Goal,
+    rule: SequentRule,
+    premises: Vec<Derivation>,
+    rest: IntoIter<Goal>,
+  },
+
+  /// 推件`goal`に可逆でない規則を試しています．失敗すれば，残りの規則`rest`を順に試します．
+  Choose {
+    goal: Goal,
+    rest: IntoIter<(SequentRule, Vec<Goal>)>,
+  },
+
+  /// 可逆でない規則を試し終えたら，LJの探索の履歴から推件を取り除きます．
+  Forget,
+}
+
+/// 推件`goal`を規則`rule`で導出するため，残りの前提の推件`rest`を順に探索します．`premises`は既に導出した前提です．
+fn infer(
+  goal: Goal,
+  rule: SequentRule,
+  premises: Vec<Derivation>,
+  mut rest: IntoIter<Goal>,
+  stack: &mut Vec<Frame>,
+) -> Next {
+  match rest.next() {
+    Some(next) => {
+      stack.push(Frame::Premises {
+        goal,
+        rule,
+        premises,
+        rest,
+      });
+      Next::Search(next)
+    }
+    None => Next::Return(Some(Derivation::new(goal.0, goal.1, rule, premises))),
+  }
+}
+
+/// 推件`goal`に，可逆でない規則`rest`を順に試します．すべて失敗すれば探索を諦めます．
+fn choose(goal: Goal, mut rest: IntoIter<(SequentRule, Vec<Goal>)>, stack: &mut Vec<Frame>) -> Next {
+  match rest.next() {
+    Some((rule, goals)) => {
+      stack.push(Frame::Choose {
+        goal: goal.clone(),
+        rest,
+      });
+      infer(goal, rule, vec![], goals.into_iter(), stack)
+    }
+    None => Next::Return(None),
+  }
+}
+
+/// シーケント計算の証明探索を行う構造です．部分の探索を待つ探索は，再帰呼び出しの代わりに[Frame]のスタックに積みます．
+struct Prover {
+  /// `true`であればLKで，そうでなければLJで探索します．
+  classical: bool,
+
+  /// LJの探索中の枝で，可逆でない規則を試した推件です．
+  history: Vec<(Vec<Logic>, Logic)>,
+
+  /// 探索全体で共有する資源の制限と，その消費量です．
+  budget: Rc<Budget>,
+
+  /// 資源の制限に達して諦めた枝があれば，その原因です．
+  exhausted: Option<Resource>,
+}
+
+impl Prover {
+  /// 推件`goal`の導出を探索します．
+  fn run(&mut self, goal: Goal) -> Option<Derivation> {
+    let mut stack = vec![];
+    let mut next = Next::Search(goal);
+    loop {
+      next = match next {
+        Next::Search(goal) => match self.budget.enter() {
+          Ok(depth) => {
+            stack.push(Frame::Leave(depth));
+            if self.classical {
+              self.lk(goal, &mut stack)
+            } else {
+              self.lj(goal, &mut stack)
+            }
+          }
+          Err(resource) => {
+            self.exhausted = Some(resource);
+            Next::Return(None)
+          }
+        },
+        Next::Return(result) => match stack.pop() {
+          Some(frame) => self.resume(frame, result, &mut stack),
+          None => return result,
+        },
+      };
+    }
+  }
+
+  /// 部分の探索の結果`result`を受け取り，探索を続けます．
+  fn resume(&mut self, frame: Frame, result: Option<Derivation>, stack: &mut Vec<Frame>) -> Next {
+    match (frame, result) {
+      (Frame::Leave(depth), result) => {
+        drop(depth);
+        Next::Return(result)
+      }
+      (Frame::Forget, result) => {
+        self.history.pop();
+        Next::Return(result)
+      }
+      (
+        Frame::Premises {
+          goal,
+          rule,
+          mut premises,
+          rest,
+        },
+        Some(derivation),
+      ) => {
+        premises.push(derivation);
+        infer(goal, rule, premises, rest, stack)
+      }
+      (Frame::Premises { .. }, None) => Next::Return(None),
+      (Frame::Choose { .. }, Some(derivation)) => Next::Return(Some(derivation)),
+      (Frame::Choose { goal, rest }, None) => choose(goal, rest, stack),
+    }
+  }
+
+  /// 推件`gamma ⊢ goal`のLJによる導出を探索します．可逆な規則を先に適用し，
+  /// 分解し尽くしたら，同じ枝に同じ推件が現れていない限り可逆でない規則を順に試します．
+  fn lj(&mut self, (gamma, delta): Goal, stack: &mut Vec<Frame>) -> Next {
+    use Logic::*;
+    let goal = &delta[0];
+    let leaf = |rule| Next::Return(Some(Derivation::new(gamma.clone(), delta.clone(), rule, vec![])));
+    let single = |gamma: Vec<Logic>, goal: &Logic| (gamma, vec![goal.clone()]);
+
+    if gamma.contains(goal) {
+      return leaf(SequentRule::Axiom);
+    }
+    if gamma.contains(&Cont) {
+      return leaf(SequentRule::ContLeft);
+    }
+
+    // 可逆な左規則です．
+    let left = gamma.iter().enumerate().find_map(|(i, logic)| match logic {
+      And(a, b) => Some((SequentRule::AndLeft, vec![single(with(&with(&without(&gamma, i), a), b), goal)])),
+      Or(a, b) => Some((
+        SequentRule::OrLeft,
+        vec![
+          single(with(&without(&gamma, i), a), goal),
+          single(with(&without(&gamma, i), b), goal),
+        ],
+      )),
+      _ => None,
+    });
+
+    // 可逆な右規則です．
+    let right = || match goal {
+      And(a, b) => Some((SequentRule::AndRight, vec![single(gamma.clone(), a), single(gamma.clone(), b)])),
+      To(a, b) => Some((SequentRule::ToRight, vec![single(with(&gamma, a), b)])),
+      Not(a) => Some((SequentRule::NotRight, vec![single(with(&gamma, a), &Cont)])),
+      Iff(a, b) => Some((
+        SequentRule::IffRight,
+        vec![single(with(&gamma, a), b), single(with(&gamma, b), a)],
+      )),
+      _ => None,
+    };
+    if let Some((rule, goals)) = left.or_else(right) {
+      return infer((gamma, delta), rule, vec![], goals.into_iter(), stack);
+    }
+
+    if self
+      .history
+      .iter()
+      .any(|(logics, logic)| logic == goal && same(logics, &gamma))
+    {
+      return Next::Return(None);
+    }
+
+    // 可逆でない規則です．
+    let mut rules = vec![];
+    if let Or(a, b) = goal {
+      for logic in [a, b] {
+        rules.push((SequentRule::OrRight, vec![single(gamma.clone(), logic)]));
+      }
+    }
+    for (i, logic) in gamma.iter().enumerate() {
+      match logic {
+        To(a, b) => rules.push((
+          SequentRule::ToLeft,
+          vec![single(gamma.clone(), a), single(with(&without(&gamma, i), b), goal)],
+        )),
+        Not(a) => rules.push((SequentRule::NotLeft, vec![single(gamma.clone(), a)])),
+        Iff(a, b) => {
+          for (a, b) in [(a, b), (b, a)] {
+            rules.push((
+              SequentRule::IffLeft,
+              vec![single(gamma.clone(), a), single(with(&gamma, b), goal)],
+            ));
+          }
+        }
+        _ => {}
+      }
+    }
+
+    self.history.push((gamma.clone(), goal.clone()));
+    stack.push(Frame::Forget);
+    choose((gamma, delta), rules.into_iter(), stack)
+  }
+
+  /// 推件`gamma ⊢ delta`のLKによる導出を探索します．後件，前件の順に，最初の分解できる論理式に規則を適用します．
+  fn lk(&mut self, (gamma, delta): Goal, stack: &mut Vec<Frame>) -> Next {
+    use Logic::*;
+    let leaf = |rule| Next::Return(Some(Derivation::new(gamma.clone(), delta.clone(), rule, vec![])));
+
+    if gamma.iter().any(|logic| delta.contains(logic)) {
+      return leaf(SequentRule::Axiom);
+    }
+    if gamma.contains(&Cont) {
+      return leaf(SequentRule::ContLeft);
+    }
+
+    let (rule, goals) = if let Some(i) = delta.iter().position(is_compound) {
+      let rest = without(&delta, i);
+      match &delta[i] {
+        Not(a) => (SequentRule::NotRight, vec![(with(&gamma, a), rest)]),
+        And(a, b) => (
+          SequentRule::AndRight,
+          vec![(gamma.clone(), with(&rest, a)), (gamma.clone(), with(&rest, b))],
+        ),
+        Or(a, b) => (SequentRule::OrRight, vec![(gamma.clone(), with(&with(&rest, a), b))]),
+        To(a, b) => (SequentRule::ToRight, vec![(with(&gamma, a), with(&rest, b))]),
+        Iff(a, b) => (
+          SequentRule::IffRight,
+          vec![(with(&gamma, a), with(&rest, b)), (with(&gamma, b), with(&rest, a))],
+        ),
+        Base(_) | Cont => unreachable!(),
+      }
+    } else if let Some(i) = gamma.iter().position(is_compound) {
+      let rest = without(&gamma, i);
+      match &gamma[i] {
+        Not(a) => (SequentRule::NotLeft, vec![(rest, with(&delta, a))]),
+        And(a, b) => (SequentRule::AndLeft, vec![(with(&with(&rest, a), b), delta.clone())]),
+        Or(a, b) => (
+          SequentRule::OrLeft,
+          vec![(with(&rest, a), delta.clone()), (with(&rest, b), delta.clone())],
+        ),
+        To(a, b) => (
+          SequentRule::ToLeft,
+          vec![(rest.clone(), with(&delta, a)), (with(&rest, b), delta.clone())],
+        ),
+        Iff(a, b) => (
+          SequentRule::IffLeft,
+          vec![
+            (with(&with(&rest, a), b), delta.clone()),
+            (rest.clone(), with(&with(&delta, a), b)),
+          ],
+        ),
+        Base(_) | Cont => unreachable!(),
+      }
+    } else {
+      return Next::Return(None);
+    };
+    infer((gamma, delta), rule, vec![], goals.into_iter(), stack)
+  }
+}
+
+impl Sequent {
+  /// 推件のシーケント計算による導出を探索します．`classical`が`true`であればLKで，そうでなければLJで導出します．
+  /// 探索は完全なので，[None]であれば（直観主義論理または古典論理上）証明不可能であることを意味します．
+  pub fn derive(&self, classical: bool) -> Option<Derivation> {
+    self.derive_with(classical, Limits::default()).unwrap_or(None)
+  }
+
+  /// 探索に用いる資源を`limits`で制限して，推件のシーケント計算による導出を探索します．
+  /// 導出が見つからず，資源の制限に達して諦めた枝があれば，その原因となった制限を返します．
+  /// LJでは，可逆でない規則の試し方が指数的に増えうるので，先にクリプキモデルの反例を探し，
+  /// 見つかれば導出を探索せずに[None]を返します．反例の探索も同じ`limits`で制限します．
+  pub fn derive_with(&self, classical: bool, limits: Limits) -> Result<Option<Derivation>, Resource> {
+    let budget = Rc::new(Budget::new(limits));
+    if !classical && Kripke::find(&self.premises, &self.conclusion, &budget)?.is_some() {
+      return Ok(None);
+    }
+
+    let mut premises = vec![];
+    for premise in &self.premises {
+      premises = with(&premises, premise);
+    }
+
+    let mut prover = Prover {
+      classical,
+      history: vec![],
+      budget,
+      exhausted: None,
+    };
+    match (prover.run((premises, vec![self.conclusion.clone()])), prover.exhausted) {
+      (None, Some(resource)) => Err(resource),
+      (derivation, _) => Ok(derivation),
+    }
+  }
+}
+
+impl Derivation {
+  /// 推件と推論規則，前提となる導出から導出を生成します．
+  fn new(
+    antecedent: Vec<Logic>,
+    succedent: Vec<Logic>,
+    rule: SequentRule,
+    premises: Vec<Derivation>,
+  ) -> Self {
+    Self {
+      antecedent,
+      succedent,
+      rule,
+      premises,
+    }
+  }
+
+  /// 導かれる推件の前件です．
+  pub fn antecedent(&self) -> &[Logic] {
+    &self.antecedent
+  }
+
+  /// 導かれる推件の後件です．
+  pub fn succedent(&self) -> &[Logic] {
+    &self.succedent
+  }
+
+  /// 用いた推論規則です．
+  pub fn rule(&self) -> SequentRule {
+    self.rule
+  }
+
+  /// 前提となる導出を，証明図で左にあるものから順に返します．公理では空です．
+  pub fn premises(&self) -> &[Derivation] {
+    &self.premises
+  }
+
+  /// 導かれる推件を，論理式の出力方法`show`を用いて`Γ ⊢ Δ`の形の文字列にします．
+  fn sequent(&self, show: impl Fn(&Logic) -> String, turnstile: &str) -> String {
+    let antecedent: Vec<_> = self.antecedent.iter().map(&show).collect();
+    let succedent: Vec<_> = self.succedent.iter().map(&show).collect();
+    format!(
+      "{} {} {}",
+      antecedent.join(", "),
+      turnstile,
+      succedent.join(", ")
+    )
+    .trim()
+    .to_string()
+  }
+
+  /// 標準出力用の証明図出力を行う関数です．
+  fn print(&self, tree: &mut String, indent: &str) {
+    let mut stack = vec![(self, String::new(), indent.to_string())];
+    while let Some((derivation, head, indent)) = stack.pop() {
+      tree.push_str(&format!(
+        "{}{} [{}]\n",
+        head,
+        derivation.sequent(Logic::to_string, "⊢"),
+        derivation.rule
+      ));
+      let premises = &derivation.premises;
+      for (i, premise) in premises.iter().enumerate().rev() {
+        let bar = if i + 1 < premises.len() { "| " } else { "  " };
+        stack.push((premise, format!("{}+ ", indent), format!("{}{}", indent, bar)));
+      }
+    }
+  }
+
+  /// TeX記法（proof.sty）用の証明図出力を行う関数です．
+  /// 前提を書き出した後に閉じかっこを書き出すため，導出と書き出す文字列を明示的なスタックに積みます．
+  fn print_tex(&self, tree: &mut String, indent: &str) {
+    let mut stack = vec![Print::Derivation(self, indent.to_string())];
+    while let Some(print) = stack.pop() {
+      let (derivation, indent) = match print {
+        Print::Derivation(derivation, indent) => (derivation, indent),
+        Print::Text(text) => {
+          tree.push_str(&text);
+          continue;
+        }
+      };
+
+      let sequent = derivation.sequent(Logic::tex, "\\vdash");
+      if derivation.premises.is_empty() {
+        tree.push_str(&format!(
+          "{}\\infer[{}]{{{}}}{{}}\n",
+          indent,
+          derivation.rule.tex(),
+          sequent
+        ));
+        continue;
+      }
+
+      tree.push_str(&format!(
+        "{}\\infer[{}]{{{}}}{{\n",
+        indent,
+        derivation.rule.tex(),
+        sequent
+      ));
+      stack.push(Print::Text(format!("{}}}\n", indent)));
+      let inner = format!("{}  ", indent);
+      for (i, premise) in derivation.premises.iter().enumerate().rev() {
+        stack.push(Print::Derivation(premise, inner.clone()));
+        if i > 0 {
+          stack.push(Print::Text(format!("{}  &\n", indent)));
+        }
+      }
+    }
+  }
+
+  /// TeX記法（bussproofs.sty）用の証明図出力を行う関数です．
+  fn print_bussproofs(&self, tree: &mut String, indent: &str) {
+    let mut stack = vec![(self, indent.to_string(), false)];
+    while let Some((derivation, indent, visited)) = stack.pop() {
+      if !visited {
+        if derivation.premises.is_empty() {
+          tree.push_str(&format!("{}  \\AxiomC{{}}\n", indent));
+        }
+        let inner = format!("{}  ", indent);
+        stack.push((derivation, indent, true));
+        for premise in derivation.premises.iter().rev() {
+          stack.push((premise, inner.clone(), false));
+        }
+        continue;
+      }
+
+      let command = match derivation.premises.len() {
+        0 | 1 => "UnaryInfC",
+        2 => "BinaryInfC",
+        _ => "TrinaryInfC",
+      };
+      tree.push_str(&format!("{}\\RightLabel{{${}$}}\n", indent, derivation.rule.tex()));
+      tree.push_str(&format!(
+        "{}\\{}{{${}$}}\n",
+        indent,
+        command,
+        derivation.sequent(Logic::tex, "\\vdash")
+      ));
+    }
+  }
+
+  /// TeX記法（bussproofs.sty）で証明図を出力します．`prooftree`環境で囲まれます．
+  pub fn bussproofs(&self) -> String {
+    let mut tree = String::from("\\begin{prooftree}\n");
+    self.print_bussproofs(&mut tree, "  ");
+    tree.push_str("\\end{prooftree}\n");
+    tree
+  }
+}
+
+/// TeX記法（proof.sty）用の証明図出力で，明示的なスタックに積む要素です．
+enum Print<'a> {
+  /// 与えられた字下げで書き出す導出です．
+  Derivation(&'a Derivation, String),
+
+  /// そのまま書き出す文字列です．
+  Text(String),
+}
+
+impl Drop for Derivation {
+  /// 深い導出でもスタックを溢れさせないよう，前提を明示的なスタックに移してから破棄します．
+  fn drop(&mut self) {
+    let mut stack = std::mem::take(&mut self.premises);
+    while let Some(mut derivation) = stack.pop() {
+      stack.append(&mut derivation.premises);
+    }
+  }
+}
+
+impl TeX for Derivation {
+  fn tex(&self) -> String {
+    let mut tree = String::new();
+    self.print_tex(&mut tree, "");
+    tree
+  }
+}
+
+impl Display for Derivation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut tree = String::new();
+    self.print(&mut tree, "");
+    write!(f, "{}", tree)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+
+  /// 論理式を前提のない推件にします．
+  fn sequent(s: &str) -> Sequent {
+    s.parse().unwrap()
+  }
+
+  #[test]
+  fn test_lj() {
+    assert_eq!(
+      sequent("A and B to B and A").derive(false).unwrap().to_string(),
+      [
+        "⊢ A ∧ B → B ∧ A [→R]",
+        "+ A ∧ B ⊢ B ∧ A [∧L]",
+        "  + A, B ⊢ B ∧ A [∧R]",
+        "    + A, B ⊢ B [Ax]",
+        "    + A, B ⊢ A [Ax]",
+        "",
+      ]
+      .join("\n")
+    );
+
+    for s in [
+      "((A or B) to C) to (A to C) and (B to C)",
+      "(A iff B) and (B iff C) to (A iff C)",
+      "not not (A or not A)",
+      "((((A to B) to A) to A) to B) to B",
+      "A to B, B to C |- A to C",
+    ] {
+      assert!(sequent(s).derive(false).is_some(), "{}", s);
+    }
+    for s in ["A or not A", "((A to B) to A) to A", "not not A to A"] {
+      assert!(sequent(s).derive(false).is_none(), "{}", s);
+      assert!(sequent(s).derive(true).is_some(), "{}", s);
+    }
+    assert!(sequent("A to B |- B").derive(true).is_none());
+    assert!(sequent("B ∨ A → ⊥ ∨ C ↔ (B ↔ C) → A ∨ ⊥ ↔ (¬ (C ↔ B) ↔ ⊥ → C → B)")
+      .derive(false)
+      .is_none());
+  }
+
+  #[test]
+  fn test_limits() {
+    let s = sequent("(A iff B) and (B iff C) to (A iff C)");
+    for classical in [false, true] {
+      let limits = Limits {
+        max_nodes: Some(3),
+        ..Limits::default()
+      };
+      assert_eq!(s.derive_with(classical, limits), Err(Resource::Nodes(3)));
+      let limits = Limits {
+        max_depth: Some(2),
+        ..Limits::default()
+      };
+      assert_eq!(s.derive_with(classical, limits), Err(Resource::Depth(2)));
+      assert!(s.derive_with(classical, Limits::default()).unwrap().is_some());
+    }
+    assert_eq!(sequent("A or not A").derive_with(false, Limits::default()), Ok(None));
+  }
+
+  #[test]
+  fn test_lk() {
+    let derivation = sequent("A or not A").derive(true).unwrap();
+    assert_eq!(
+      derivation.to_string(),
+      "⊢ A ∨ ¬ A [∨R]\n+ ⊢ A, ¬ A [¬R]\n  + A ⊢ A [Ax]\n"
+    );
+    assert_eq!(
+      derivation.tex(),
+      [
+        "\\infer[\\lor R]{\\vdash A \\lor \\lnot A}{",
+        "  \\infer[\\lnot R]{\\vdash A, \\lnot A}{",
+        "    \\infer[\\mathrm{Ax}]{A \\vdash A}{}",
+        "  }",
+        "}",
+        "",
+      ]
+      .join("\n")
+    );
+    assert_eq!(
+      derivation.bussproofs(),
+      [
+        "\\begin{prooftree}",
+        "        \\AxiomC{}",
+        "      \\RightLabel{$\\mathrm{Ax}$}",
+        "      \\UnaryInfC{$A \\vdash A$}",
+        "    \\RightLabel{$\\lnot R$}",
+        "    \\UnaryInfC{$\\vdash A, \\lnot A$}",
+        "  \\RightLabel{$\\lor R$}",
+        "  \\UnaryInfC{$\\vdash A \\lor \\lnot A$}",
+        "\\end{prooftree}",
+        "",
+      ]
+      .join("\n")
+    );
+  }
+}
@@ -0,0 +1,367 @@
+//! 証明図が自然演繹の正しい導出であるかを，ソルバとは独立に検査するモジュールです．
+//! 各推論が主張する推論規則に従っているか，仮定の参照番号がそれを閉じる推論と一致するか，
+//! 閉じられていない仮定が残っていないかを確かめます．
+
+use std::error::Error;
+use std::fmt::Display;
+
+use super::{logic::*, proof::*, solver::{Classical, Rule}};
+
+impl Proof {
+  /// 証明図を検査します．`premises`は推件の前提として，閉じずに用いてよい論理式です．
+  /// 古典論理の規則は，`classical`で指定されたものだけを用いることができます．
+  /// 誤りがあれば，最初に見つかった誤りを[ProofError]として返します．
+  pub fn check(&self, premises: &[Logic], classical: Option<Classical>) -> Result<(), ProofError> {
+    Checker {
+      premises,
+      classical,
+      line: 0,
+      scope: Vec::new(),
+    }
+    .check(self)
+  }
+
+  /// 証明図が推件`sequent`の証明であるかを検査します．根の論理式が結論と一致し，
+  /// 前提以外の仮定がすべて閉じられていることを確かめます．
+  pub fn check_sequent(&self, sequent: &Sequent, classical: Option<Classical>) -> Result<(), ProofError> {
+    if self.conclusion() != &sequent.conclusion {
+      return Err(ProofError {
+        line: 1,
+        logic: self.conclusion().clone(),
+        kind: ProofErrorKind::Conclusion(sequent.conclusion.clone()),
+      });
+    }
+    self.check(&sequent.premises, classical)
+  }
+
+  /// この推論の各前提の中で閉じられる仮定を，前提と同じ順に返します．閉じる仮定がなければ[None]です．
+  /// 仮定を閉じずに導入する推論（空の閉じ）でも，推論規則が閉じうる仮定を返します．
+  /// 推論規則に従っていない推論では，すべて[None]になります．
+  pub fn hypotheses(&self) -> Vec<Option<Logic>> {
+    match self.rule() {
+      Some(rule) => discharged(rule, self.conclusion(), self.premises())
+        .unwrap_or_else(|_| vec![None; self.premises().len()]),
+      None => vec![],
+    }
+  }
+}
+
+/// 証明図を根から行きがけ順にたどって検査する構造です．
+struct Checker<'a> {
+  /// 推件の前提です．
+  premises: &'a [Logic],
+
+  /// 用いてよい古典論理の規則です．
+  classical: Option<Classical>,
+
+  /// 最後に検査した推論の行番号です．行番号は標準出力用の証明図の行と一致します．
+  line: usize,
+
+  /// 検査している推論より下で閉じられる仮定の，参照番号と論理式の組です．
+  scope: Vec<(usize, Logic)>,
+}
+
+/// 検査の途中で，これから行うことです．
+enum Visit<'a> {
+  /// 推論を検査します．仮定を閉じる前提であれば，その参照番号と論理式を持ちます．
+  Check(&'a Proof, Option<(usize, Logic)>),
+
+  /// 前提の検査を終え，その前提の中で閉じられる仮定を取り除きます．
+  Leave,
+}
+
+impl Checker<'_> {
+  /// 推論`proof`とその前提を検査します．
+  /// 深い証明図でもスタックを溢れさせないよう，検査する推論を明示的なスタックに積んで行きがけ順にたどります．
+  fn check(&mut self, proof: &Proof) -> Result<(), ProofError> {
+    let mut stack = vec![Visit::Check(proof, None)];
+    while let Some(visit) = stack.pop() {
+      let proof = match visit {
+        Visit::Check(proof, scoped) => {
+          self.scope.extend(scoped);
+          proof
+        }
+        Visit::Leave => {
+          self.scope.pop();
+          continue;
+        }
+      };
+
+      self.line += 1;
+      let err = |kind| ProofError {
+        line: self.line,
+        logic: proof.conclusion().clone(),
+        kind,
+      };
+
+      match proof.step() {
+        Step::Assumption(label) => match self.scope.iter().rev().find(|(l, _)| l == label) {
+          None => return Err(err(ProofErrorKind::Undischarged(*label))),
+          Some((_, logic)) if logic != proof.conclusion() => {
+            return Err(err(ProofErrorKind::WrongAssumption(*label, logic.clone())))
+          }
+          Some(_) => (),
+        },
+        Step::Premise if self.premises.contains(proof.conclusion()) => (),
+        Step::Premise => return Err(err(ProofErrorKind::NotPremise)),
+        Step::Inference {
+          rule,
+          label,
+          premises,
+        } => {
+          if rule.classical().is_some() && rule.classical() != self.classical {
+            return Err(err(ProofErrorKind::Classical(*rule)));
+          }
+          let discharged = discharged(*rule, proof.conclusion(), premises).map_err(err)?;
+          if label.is_some() && discharged.iter().all(Option::is_none) {
+            return Err(err(ProofErrorKind::NoDischarge(*rule)));
+          }
+
+          for (premise, assumption) in premises.iter().zip(discharged).rev() {
+            match (label, assumption) {
+              (Some(label), Some(assumption)) => {
+                stack.push(Visit::Leave);
+                stack.push(Visit::Check(premise, Some((*label, assumption))));
+              }
+              _ => stack.push(Visit::Check(premise, None)),
+            }
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+/// `premises`から推論規則`rule`で`conclusion`を推論できるかを確かめ，
+/// できれば各前提の中で閉じられる仮定を返します．
+fn discharged(
+  rule: Rule,
+  conclusion: &Logic,
+  premises: &[Proof],
+) -> Result<Vec<Option<Logic>>, ProofErrorKind> {
+  if premises.len() != rule.arity() {
+    return Err(ProofErrorKind::Arity(rule));
+  }
+
+  use Logic::*;
+  let premises: Vec<&Logic> = premises.iter().map(Proof::conclusion).collect();
+  let discharged = match (rule, conclusion, premises.as_slice()) {
+    (Rule::NotIntro, Not(a), [Cont]) => vec![Some(a.as_ref().clone())],
+    (Rule::NotElim, Cont, [a, Not(b)]) if a == &b.as_ref() => vec![None, None],
+    (Rule::AndIntro, And(a, b), [c, d]) if a.as_ref() == *c && b.as_ref() == *d => vec![None, None],
+    (Rule::AndElim, c, [And(a, b)]) if c == a.as_ref() || c == b.as_ref() => vec![None],
+    (Rule::OrIntro, Or(a, b), [c]) if a.as_ref() == *c || b.as_ref() == *c => vec![None],
+    (Rule::OrElim, c, [Or(a, b), d, e]) if c == *d && c == *e => vec![
+      None,
+      Some(a.as_ref().clone()),
+      Some(b.as_ref().clone()),
+    ],
+    (Rule::ToIntro, To(a, b), [c]) if b.as_ref() == *c => vec![Some(a.as_ref().clone())],
+    (Rule::ToElim, b, [a, To(c, d)]) if *a == c.as_ref() && b == d.as_ref() => vec![None, None],
+    (Rule::IffIntro, Iff(a, b), [c, d]) if b.as_ref() == *c && a.as_ref() == *d => vec![
+      Some(a.as_ref().clone()),
+      Some(b.as_ref().clone()),
+    ],
+    (Rule::IffElim, c, [d, Iff(a, b)])
+      if (*d == a.as_ref() && c == b.as_ref()) || (*d == b.as_ref() && c == a.as_ref()) =>
+    {
+      vec![None, None]
+    }
+    (Rule::ContElim, _, [Cont]) => vec![None],
+    (Rule::ExcludedMiddle, Or(a, b), []) if **b == Not(a.clone()) => vec![],
+    (Rule::DoubleNegation, a, [Not(b)]) if **b == Not(Box::new(a.clone())) => vec![None],
+    (Rule::Reductio, a, [Cont]) => vec![Some(Not(Box::new(a.clone())))],
+    _ => return Err(ProofErrorKind::Form(rule)),
+  };
+  Ok(discharged)
+}
+
+/// 証明図の誤りです．誤りのある推論の行番号と論理式，誤りの種類を持ちます．
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofError {
+  /// 誤りのある推論の行番号です．標準出力用の証明図で，根を1行目として数えます．
+  pub line: usize,
+
+  /// 誤りのある推論の論理式です．
+  pub logic: Logic,
+
+  /// 誤りの種類です．
+  pub kind: ProofErrorKind,
+}
+
+/// 証明図の誤りの種類を示す列挙子です．
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofErrorKind {
+  /// 推論規則がとる前提の数と，推論の前提の数が一致しません．
+  Arity(Rule),
+
+  /// 前提と結論の形が推論規則に従っていません．
+  Form(Rule),
+
+  /// 仮定を閉じない推論規則に参照番号が付いています．
+  NoDischarge(Rule),
+
+  /// 仮定の参照番号を持つ推論が，その仮定より下にありません．
+  Undischarged(usize),
+
+  /// 仮定の参照番号を持つ推論が閉じる論理式と，仮定の論理式が一致しません．
+  WrongAssumption(usize, Logic),
+
+  /// 推件の前提として与えられていない論理式が，閉じられない仮定として用いられています．
+  NotPremise,
+
+  /// 根の論理式が，証明すべき推件の結論と一致しません．
+  Conclusion(Logic),
+
+  /// 用いてよいと指定されていない古典論理の規則で推論しています．
+  Classical(Rule),
+}
+
+impl Display for ProofErrorKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Arity(rule) => write!(f, "[{}] takes {} premise(s)", rule, rule.arity()),
+      Self::Form(rule) => write!(f, "does not follow from its premises by [{}]", rule),
+      Self::NoDischarge(rule) => write!(f, "[{}] does not discharge any assumption", rule),
+      Self::Undischarged(label) => {
+        write!(f, "is not discharged: no inference below has label {}", label)
+      }
+      Self::WrongAssumption(label, logic) => {
+        write!(f, "is not the assumption {} discharged by {}", logic, label)
+      }
+      Self::NotPremise => write!(f, "is an open assumption but not a premise"),
+      Self::Conclusion(logic) => write!(f, "is not the conclusion {}", logic),
+      Self::Classical(rule) => write!(f, "uses [{}], which is not enabled", rule),
+    }
+  }
+}
+
+impl Display for ProofError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "line {}: {} {}", self.line, self.logic, self.kind)
+  }
+}
+
+impl Error for ProofError {}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+  use crate::solver::Classical;
+
+  #[test]
+  fn test_solver() {
+    for s in [
+      "((A or B) to C) to (A to C) and (B to C)",
+      "(A iff B) and (B iff C) to (A iff C)",
+      "not not (A or not A)",
+      "((((A to B) to A) to A) to B) to B",
+      "(A or B) and (C and D) to D",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      let inference = logic.solve(None).unwrap();
+      assert_eq!(inference.to_proof().check(&[], None), Ok(()), "{}", s);
+      assert_eq!(inference.normalize().to_proof().check(&[], None), Ok(()), "{}", s);
+    }
+
+    let logic = Logic::new("((A to B) to A) to A").unwrap();
+    for classical in [
+      Classical::ExcludedMiddle,
+      Classical::DoubleNegation,
+      Classical::Reductio,
+    ] {
+      let proof = logic.solve(Some(classical)).unwrap().to_proof();
+      assert_eq!(proof.check(&[], Some(classical)), Ok(()));
+      for other in [
+        None,
+        Some(Classical::ExcludedMiddle),
+        Some(Classical::DoubleNegation),
+        Some(Classical::Reductio),
+      ] {
+        if other != Some(classical) {
+          assert!(matches!(
+            proof.check(&[], other).unwrap_err().kind,
+            ProofErrorKind::Classical(_)
+          ));
+        }
+      }
+    }
+
+    let sequent: Sequent = "A to B, A |- B".parse().unwrap();
+    let proof = sequent.solve(None).unwrap().to_proof();
+    assert_eq!(proof.check(&sequent.premises, None), Ok(()));
+    assert_eq!(
+      proof.check(&[], None).unwrap_err().kind,
+      ProofErrorKind::NotPremise
+    );
+  }
+
+  #[test]
+  fn test_error() {
+    let a = || Logic::new("A").unwrap();
+    let b = || Logic::new("B").unwrap();
+    let leaf = |logic: Logic, label| Proof::new(logic, Step::Assumption(label));
+    let infer = |logic: Logic, rule, label, premises| {
+      Proof::new(
+        logic,
+        Step::Inference {
+          rule,
+          label,
+          premises,
+        },
+      )
+    };
+
+    let proof = infer(
+      Logic::new("A to A and B").unwrap(),
+      Rule::ToIntro,
+      Some(1),
+      vec![infer(
+        Logic::new("A and B").unwrap(),
+        Rule::AndIntro,
+        None,
+        vec![leaf(a(), 1), leaf(a(), 1)],
+      )],
+    );
+    let err = proof.check(&[], None).unwrap_err();
+    assert_eq!((err.line, err.kind), (2, ProofErrorKind::Form(Rule::AndIntro)));
+
+    let proof = infer(
+      Logic::new("A to B to A").unwrap(),
+      Rule::ToIntro,
+      Some(1),
+      vec![infer(
+        Logic::new("B to A").unwrap(),
+        Rule::ToIntro,
+        Some(2),
+        vec![leaf(a(), 2)],
+      )],
+    );
+    let err = proof.check(&[], None).unwrap_err();
+    assert_eq!((err.line, err.kind), (3, ProofErrorKind::WrongAssumption(2, b())));
+    assert_eq!(
+      proof.check(&[], None).unwrap_err().to_string(),
+      "line 3: A is not the assumption B discharged by 2"
+    );
+
+    let proof = infer(
+      Logic::new("B to A").unwrap(),
+      Rule::ToIntro,
+      Some(1),
+      vec![leaf(a(), 2)],
+    );
+    let err = proof.check(&[], None).unwrap_err();
+    assert_eq!((err.line, err.kind), (2, ProofErrorKind::Undischarged(2)));
+
+    let proof = infer(a(), Rule::AndElim, Some(1), vec![leaf(a(), 1)]);
+    let err = proof.check(&[], None).unwrap_err();
+    assert_eq!((err.line, err.kind), (1, ProofErrorKind::Form(Rule::AndElim)));
+
+    let proof = infer(a(), Rule::ToElim, None, vec![leaf(a(), 1)]);
+    let err = proof.check(&[], None).unwrap_err();
+    assert_eq!((err.line, err.kind), (1, ProofErrorKind::Arity(Rule::ToElim)));
+  }
+}
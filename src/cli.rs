@@ -5,6 +5,7 @@
 //! `exec`メソッドによって，解析された引数に則って実行できます．
 //! #Examples
 //! ```no_run
+//! # use prop_logic::Args;
 //! #[paw::main]
 //! fn main(args: Args) {
 //!   if let Err(e) = args.exec() {
@@ -16,6 +17,7 @@
 //! 
 
 use std::path::PathBuf;
+use std::time::Duration;
 use structopt::StructOpt;
 
 use crate::exec::*;
@@ -31,10 +33,70 @@ pub struct Args {
   /// text input (if omitted, starts in interactive mode)
   input: Option<String>,
 
-  /// output in TeX format (bussproof.sty)
-  #[structopt(short, long)]
+  /// output in TeX format (proof.sty); same as `--format proof`
+  #[structopt(short, long, conflicts_with = "format")]
   tex: bool,
 
+  /// output format (plain, proof, bussproofs, fitch or fitch-tex)
+  #[structopt(short, long, default_value = "plain")]
+  format: Format,
+
+  /// solve or check in classical logic with the given rule (lem, dne or raa)
+  #[structopt(short, long)]
+  classical: Option<Classical>,
+
+  /// normalize the proof by eliminating detours
+  #[structopt(short, long, conflicts_with = "sequent-calculus")]
+  normalize: bool,
+
+  /// output a derivation in sequent calculus (LJ, or LK if classical)
+  #[structopt(short, long)]
+  sequent_calculus: bool,
+
+  /// output a Hilbert-style derivation from axioms by modus ponens
+  #[structopt(short, long, conflicts_with = "sequent-calculus")]
+  axiomatic: bool,
+
+  /// abort the search beyond the given depth of nested subgoals
+  #[structopt(long)]
+  max_depth: Option<usize>,
+
+  /// abort the search after exploring the given number of subgoals
+  #[structopt(long)]
+  max_nodes: Option<usize>,
+
+  /// abort the search after the given number of milliseconds
+  #[structopt(long)]
+  timeout: Option<u64>,
+
+  /// how to report counterexamples (first, all or dnf)
+  #[structopt(short, long, default_value = "first")]
+  report: Report,
+
+  /// print the truth table of the input and all its subformulas instead of a proof
+  #[structopt(long, conflicts_with_all = &["countermodel", "check"])]
+  table: bool,
+
+  /// truth table format (plain, markdown, csv or tex)
+  #[structopt(long, default_value = "plain")]
+  table_format: TableFormat,
+
+  /// print a Kripke countermodel of an intuitionistically unprovable input instead of a proof
+  #[structopt(long, conflicts_with = "check")]
+  countermodel: bool,
+
+  /// countermodel format (plain, graphviz or tikz)
+  #[structopt(long, default_value = "plain")]
+  model_format: ModelFormat,
+
+  /// check a natural-deduction proof of the input written in the plain output format
+  #[structopt(long, requires = "input")]
+  check: bool,
+
+  /// file containing the proof to check (if omitted, stdin)
+  #[structopt(long, parse(from_os_str), requires = "check")]
+  proof: Option<PathBuf>,
+
   /// output file (if omitted, stdout)
   #[structopt(short, long, parse(from_os_str))]
   out: Option<PathBuf>,
@@ -42,33 +104,100 @@ pub struct Args {
 
 impl Args {
   /// 解析されたコマンドラインの命令を実行します．詳しくは[このモジュールの説明](self)を参照してください．
+  /// 入力が省略されていれば，1行ずつ読み込んで同じ命令を繰り返します．
   pub fn exec(&self) -> Result<(), ExecError> {
     if let Some(ref input) = self.input {
-      let res = exec(input, self.tex)?;
+      let res = self.run(input)?;
+      return self.write(res);
+    }
 
-      match self.out {
-        Some(ref path) => std::fs::write(path, res)?,
-        None => println!("{}", res),
+    loop {
+      println!("input ('quit' to quit):");
+      let mut input = String::new();
+      std::io::stdin().read_line(&mut input)?;
+
+      if input.starts_with("quit") {
+        return Ok(());
+      }
+
+      let res = self.run(&input)?;
+      self.write(res)?;
+    }
+  }
+
+  /// 入力`input`に対して，指定された命令を実行します．
+  /// `--table`，`--countermodel`，`--check`のいずれも指定されていなければ，証明図を出力します．
+  fn run(&self, input: &str) -> Result<String, ExecError> {
+    if self.table {
+      return exec_table(input, self.table_format);
+    }
+    if self.countermodel {
+      return exec_countermodel(input, self.model_format, self.options().limits);
+    }
+    if self.check {
+      let proof = match self.proof {
+        Some(ref path) => std::fs::read_to_string(path)?,
+        None => std::io::read_to_string(std::io::stdin())?,
       };
+      return exec_check(input, &proof, self.classical);
+    }
 
-      Ok(())
-    } else {
-      loop {
-        println!("input ('quit' to quit):");
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
+    exec(input, &self.options())
+  }
 
-        if input.starts_with("quit") {
-          return Ok(());
-        }
+  /// 証明図の出力に関する引数を[ExecOptions]にまとめます．
+  fn options(&self) -> ExecOptions {
+    ExecOptions {
+      format: if self.tex { Format::Proof } else { self.format },
+      classical: self.classical,
+      normalize: self.normalize,
+      system: if self.sequent_calculus {
+        System::SequentCalculus
+      } else if self.axiomatic {
+        System::Hilbert
+      } else {
+        System::NaturalDeduction
+      },
+      limits: Limits {
+        max_depth: self.max_depth,
+        max_nodes: self.max_nodes,
+        timeout: self.timeout.map(Duration::from_millis),
+        cancel: None,
+      },
+      report: self.report,
+    }
+  }
 
-        let res = exec(&input, self.tex)?;
+  /// 実行結果を，出力ファイルが指定されていればそのファイルに，そうでなければ標準出力に書き出します．
+  fn write(&self, res: String) -> Result<(), ExecError> {
+    match self.out {
+      Some(ref path) => std::fs::write(path, res)?,
+      None => println!("{}", res),
+    };
 
-        match self.out {
-          Some(ref path) => std::fs::write(path, res)?,
-          None => println!("{}", res),
-        };
-      }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+
+  #[test]
+  fn test_input() {
+    for input in ["check to check", "table", "countermodel"] {
+      let args = Args::from_iter_safe(["prop-logic", input]).unwrap();
+      assert_eq!(args.input.as_deref(), Some(input));
+      assert!(!args.table && !args.countermodel && !args.check);
     }
+
+    let args = Args::from_iter_safe(["prop-logic", "--table", "table"]).unwrap();
+    assert!(args.table);
+    assert_eq!(args.input.as_deref(), Some("table"));
+    assert!(Args::from_iter_safe(["prop-logic", "--table", "--countermodel", "A"]).is_err());
+    assert!(Args::from_iter_safe(["prop-logic", "--check"]).is_err());
+    assert!(Args::from_iter_safe(["prop-logic", "-n", "-s", "A"]).is_err());
   }
 }
@@ -3,41 +3,227 @@
 
 use std::error::Error;
 use std::fmt::Display;
+use std::str::FromStr;
 
+use super::checker::ProofError;
 use super::logic::*;
 use super::parser::ParseLogicError;
-use super::solver::SolveError;
+use super::proof::Proof;
+use super::solver::{SolveError, Stuck};
+use super::table::TruthTable;
 use super::TeX;
 
-/// 入力された文字列から論理式をパースし，ソルバを呼び出し，設定に則って出力します．
-pub fn exec(input: &String, tex: bool) -> Result<String, ExecError> {
-  // Logic::from(&str) as FromStr を呼び出しています．
-  let logic: Logic = input.parse()?;
+pub use super::kripke::ModelFormat;
+pub use super::logic::Report;
+pub use super::budget::Limits;
+pub use super::solver::Classical;
+pub use super::table::TableFormat;
+
+/// 証明図の出力形式を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+  /// 簡略化した記法です．
+  Plain,
+
+  /// proof.styの`\infer`を用いたTeX記法です．
+  Proof,
+
+  /// bussproofs.styの`\AxiomC`，`\UnaryInfC`等を用いたTeX記法です．
+  Bussproofs,
+
+  /// 副証明を縦線で示す，簡略化したFitch式の記法です．
+  Fitch,
 
-  logic.check_all()?;
+  /// fitch.styの`\hypo`，`\have`等を用いたFitch式のTeX記法です．
+  FitchTex,
+}
+
+impl FromStr for Format {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "plain" => Ok(Self::Plain),
+      "proof" => Ok(Self::Proof),
+      "bussproofs" => Ok(Self::Bussproofs),
+      "fitch" => Ok(Self::Fitch),
+      "fitch-tex" => Ok(Self::FitchTex),
+      _ => Err(format!(
+        "unknown format: {} (expected plain, proof, bussproofs, fitch or fitch-tex)",
+        s
+      )),
+    }
+  }
+}
 
-  let inference = logic.solve()?;
+/// 出力する証明の体系を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum System {
+  /// 自然演繹の証明図です．
+  NaturalDeduction,
 
-  Ok(if tex {
-    inference.tex()
-  } else {
-    inference.to_string()
+  /// シーケント計算（LJ，古典論理ではLK）の導出です．
+  SequentCalculus,
+
+  /// ヒルベルト流の公理系による導出です．
+  Hilbert,
+}
+
+/// 証明図の出力に関する設定をまとめた構造です．
+/// 既定では，直観主義論理で証明し，正規化せずに自然演繹の証明図を簡略化した記法で出力します．
+#[derive(Debug, Clone)]
+pub struct ExecOptions {
+  /// 出力形式です．
+  pub format: Format,
+
+  /// 古典論理で証明する場合に加える規則です．[None]であれば直観主義論理で証明します．
+  pub classical: Option<Classical>,
+
+  /// `true`であれば，得られた証明図を正規化してから出力します．シーケント計算の導出には適用されません．
+  pub normalize: bool,
+
+  /// 出力する証明の体系です．
+  pub system: System,
+
+  /// 証明探索に用いる資源の制限です．
+  pub limits: Limits,
+
+  /// 古典論理上証明不可能な場合の反例の報告方法です．
+  pub report: Report,
+}
+
+impl Default for ExecOptions {
+  fn default() -> Self {
+    Self {
+      format: Format::Plain,
+      classical: None,
+      normalize: false,
+      system: System::NaturalDeduction,
+      limits: Limits::default(),
+      report: Report::First,
+    }
+  }
+}
+
+/// 入力された文字列から論理式または推件をパースし，ソルバを呼び出し，`options`に則って出力します．
+/// `options.limits`で証明探索に用いる資源を制限し，制限に達すれば探索を打ち切ります．
+/// `options.system`に従って，自然演繹の証明図，シーケント計算の導出，またはヒルベルト流の導出を出力します．
+/// ヒルベルト流の導出は，正規化した後の証明図から翻訳します．
+/// 古典論理上証明不可能であれば，`options.report`に従って反例を報告します．
+pub fn exec(input: &str, options: &ExecOptions) -> Result<String, ExecError> {
+  let ExecOptions {
+    format,
+    classical,
+    normalize,
+    system,
+    ref limits,
+    report,
+  } = *options;
+
+  // Sequent::from(&str) as FromStr を呼び出しています．
+  let sequent: Sequent = input.parse()?;
+
+  sequent.check(report)?;
+
+  if system == System::SequentCalculus {
+    // LJの探索は証明探索とは別のアルゴリズムなので，導出できなければ証明探索で失敗した箇所を報告します．
+    let derivation = sequent
+      .derive_with(classical.is_some(), limits.clone())
+      .map_err(SolveError::ResourceExhausted)?;
+    let derivation = match derivation {
+      Some(derivation) => derivation,
+      None => {
+        sequent.solve_with(classical, limits.clone())?;
+        return Err(ExecError::NotDerivable(sequent));
+      }
+    };
+    return Ok(match format {
+      Format::Plain | Format::Fitch => derivation.to_string(),
+      Format::Proof | Format::FitchTex => derivation.tex(),
+      Format::Bussproofs => derivation.bussproofs(),
+    });
+  }
+
+  let mut inference = sequent.solve_with(classical, limits.clone())?;
+  if normalize {
+    inference = inference.normalize();
+  }
+  if system == System::Hilbert {
+    let hilbert = inference.hilbert()?;
+    return Ok(match format {
+      Format::Plain | Format::Fitch => hilbert.to_string(),
+      Format::Proof | Format::Bussproofs | Format::FitchTex => hilbert.tex(),
+    });
+  }
+
+  Ok(match format {
+    Format::Plain => inference.to_string(),
+    Format::Proof => inference.tex(),
+    Format::Bussproofs => inference.bussproofs(),
+    Format::Fitch => inference.fitch(),
+    Format::FitchTex => inference.fitch_tex(),
   })
 }
 
+/// 入力された文字列から論理式をパースし，その真理値表を設定に則って出力します．
+pub fn exec_table(input: &str, format: TableFormat) -> Result<String, ExecError> {
+  let logic: Logic = input.parse()?;
+
+  Ok(TruthTable::new(&logic)?.print(format))
+}
+
+/// 入力された文字列から推件と，標準出力用の証明図と同じ形式の証明図`proof`をパースし，
+/// 証明図がその推件の正しい証明であるかを検査します．正しければその旨を出力します．
+/// 古典論理の規則は，`classical`で指定されたものだけを用いることができます．
+pub fn exec_check(input: &str, proof: &str, classical: Option<Classical>) -> Result<String, ExecError> {
+  let sequent: Sequent = input.parse()?;
+  let parsed: Proof = proof.parse()?;
+
+  parsed.check_sequent(&sequent, classical).map_err(|mut err| {
+    // 検査器は空行を数えないため，入力の行番号に直します．
+    let mut lines = proof.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    if let Some((i, _)) = lines.nth(err.line - 1) {
+      err.line = i + 1;
+    }
+    err
+  })?;
+
+  Ok(format!("valid proof of {}", sequent))
+}
+
+/// 入力された文字列から論理式または推件をパースし，その反例となるクリプキモデルを設定に則って出力します．
+/// 直観主義論理上証明可能であれば，反例は存在しないためエラーになります．
+/// `limits`で反例の探索に用いる資源を制限し，制限に達すれば探索を打ち切ります．
+pub fn exec_countermodel(input: &str, format: ModelFormat, limits: Limits) -> Result<String, ExecError> {
+  let sequent: Sequent = input.parse()?;
+
+  match sequent.countermodel_with(limits).map_err(SolveError::ResourceExhausted)? {
+    Some(model) => Ok(model.print(format)),
+    None => Err(ExecError::Provable(sequent)),
+  }
+}
+
 /// 実行時のエラーをまとめた列挙子です．
 #[derive(Debug)]
 pub enum ExecError {
   /// 入力文字列をパースした場合のエラーです．
   ParseError(ParseLogicError),
 
-  /// 入力された論理式が古典論理上証明不可能である場合のエラーです．
+  /// 入力された論理式が古典論理上証明不可能である（推件であれば，前提から結論が帰結しない）場合のエラーです．
   CheckError(CheckError),
 
-  /// 入力された論理式を証明できなかった場合のエラーです．必ずしも直観主義論理上証明不可能な命題であることを意味しません．
+  /// 入力された論理式を証明できなかった場合のエラーです．直観主義論理（古典論理の規則を選んだ場合は古典論理）上証明不可能な命題であることを意味します．
   SolveError(SolveError),
 
-  /// 出力形式をファイルにした際に出力できなかった場合のエラーです．
+  /// 入力された証明図が推論規則に従っていない場合のエラーです．
+  ProofError(ProofError),
+
+  /// 反例を求められた推件が直観主義論理上証明可能である場合のエラーです．
+  Provable(Sequent),
+
+  /// 推件のシーケント計算による導出が見つからなかった場合のエラーです．
+  NotDerivable(Sequent),
+
+  /// 出力形式をファイルにした際に出力できなかった場合や，証明図のファイルを読み込めなかった場合のエラーです．
   FileError(std::io::Error),
 }
 
@@ -59,19 +245,69 @@ impl From<SolveError> for ExecError {
   }
 }
 
+impl From<ProofError> for ExecError {
+  fn from(e: ProofError) -> Self {
+    Self::ProofError(e)
+  }
+}
+
 impl From<std::io::Error> for ExecError {
   fn from(e: std::io::Error) -> Self {
     Self::FileError(e)
   }
 }
 
+/// 証明に失敗した箇所を，その箇所で用いることができた仮定と，試みた推論規則とともに報告します．
+/// 反例となるクリプキモデルがあれば添えます．
+fn stuck(e: &Stuck) -> String {
+  let show = |logics: Vec<String>| {
+    if logics.is_empty() {
+      "none".to_string()
+    } else {
+      logics.join(", ")
+    }
+  };
+  let assumptions = e.assumptions().iter().map(|logic| logic.to_string()).collect();
+  let attempts = e
+    .attempts()
+    .iter()
+    .map(|(rule, logic)| format!("{} on {}", rule, logic))
+    .collect();
+
+  let mut res = format!(
+    "could not infer: {}\nstuck here:\n  assumptions in scope: {}\n  rules tried: {}",
+    e.logic(),
+    show(assumptions),
+    show(attempts)
+  );
+  if let Some(model) = e.countermodel() {
+    res.push_str("\nKripke countermodel of the input:");
+    for line in model.to_string().lines() {
+      res.push_str(&format!("\n  {}", line));
+    }
+  }
+  res
+}
+
 impl Display for ExecError {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
       Self::ParseError(e) => write!(f, "error when parsing:\n{}", e),
       Self::CheckError(e) => write!(f, "error when checking:\n{}", e),
+      Self::SolveError(SolveError::Unprovable(e)) => write!(f, "error when solving:\n{}", stuck(e)),
       Self::SolveError(e) => write!(f, "error when solving:\n{}", e),
-      Self::FileError(e) => write!(f, "error when writing file:\n{}", e),
+      Self::ProofError(e) => write!(f, "error when checking proof:\n{}", e),
+      Self::Provable(s) => write!(
+        f,
+        "error when finding countermodel:\n{} is provable in intuitionistic logic",
+        s
+      ),
+      Self::NotDerivable(s) => write!(
+        f,
+        "error when deriving:\n{} could not be derived in sequent calculus",
+        s
+      ),
+      Self::FileError(e) => write!(f, "error when reading or writing file:\n{}", e),
     }
   }
 }
@@ -82,7 +318,164 @@ impl Error for ExecError {
       Self::ParseError(e) => Some(e),
       Self::CheckError(e) => Some(e),
       Self::SolveError(e) => Some(e),
+      Self::ProofError(e) => Some(e),
+      Self::Provable(_) | Self::NotDerivable(_) => None,
       Self::FileError(e) => Some(e),
     }
   }
 }
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+
+  /// 実行結果を，エラーであればそのメッセージとして返します．
+  fn run(input: &str, format: Format, classical: Option<Classical>, report: Report) -> String {
+    let options = ExecOptions {
+      format,
+      classical,
+      report,
+      ..ExecOptions::default()
+    };
+    exec(input, &options).unwrap_or_else(|err| err.to_string())
+  }
+
+  #[test]
+  fn test_deterministic() {
+    let inputs = [
+      "((A or B) to C) to (A to C) and (B to C)",
+      "(A or B) and (C or D) to (A or C) and (B or D) or A and D or B and C",
+      "(A iff B) and (B iff C) to (A iff C)",
+      "A to B, B to C, C to D, D to E |- A to E",
+      "not not A to A",
+      "(P or Q) and R to P",
+    ];
+    let formats = [
+      Format::Plain,
+      Format::Proof,
+      Format::Bussproofs,
+      Format::Fitch,
+      Format::FitchTex,
+    ];
+    let classicals = [
+      None,
+      Some(Classical::ExcludedMiddle),
+      Some(Classical::DoubleNegation),
+      Some(Classical::Reductio),
+    ];
+    let reports = [Report::First, Report::All, Report::Dnf];
+
+    for input in inputs {
+      for format in formats {
+        for classical in classicals {
+          for report in reports {
+            let expected = run(input, format, classical, report);
+            for _ in 0..8 {
+              assert_eq!(run(input, format, classical, report), expected, "{}", input);
+            }
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_snapshot() {
+    assert_eq!(
+      run(
+        "(A iff B) and (B iff C) to (A iff C)",
+        Format::Plain,
+        None,
+        Report::First
+      ),
+      [
+        "(A ↔ B) ∧ (B ↔ C) → (A ↔ C) [→I] : 1",
+        "+ A ↔ C [↔I] : 2",
+        "  + C [↔E]",
+        "  | + B [↔E]",
+        "  | | + A from: 2",
+        "  | | + A ↔ B [∧E]",
+        "  | |   + (A ↔ B) ∧ (B ↔ C) from: 1",
+        "  | + B ↔ C [∧E]",
+        "  |   + (A ↔ B) ∧ (B ↔ C) from: 1",
+        "  + A [↔E]",
+        "    + B [↔E]",
+        "    | + C from: 2",
+        "    | + B ↔ C [∧E]",
+        "    |   + (A ↔ B) ∧ (B ↔ C) from: 1",
+        "    + A ↔ B [∧E]",
+        "      + (A ↔ B) ∧ (B ↔ C) from: 1",
+        "",
+      ]
+      .join("\n")
+    );
+    assert_eq!(
+      run("(P or Q) and R to P", Format::Plain, None, Report::First),
+      "error when checking:\n(P ∨ Q) ∧ R → P turns out false when: P: false, Q: true, R: true"
+    );
+    assert_eq!(
+      run("A or not A", Format::Plain, None, Report::First),
+      [
+        "error when solving:",
+        "could not infer: A ∨ ¬ A",
+        "stuck here:",
+        "  assumptions in scope: none",
+        "  rules tried: ∨I on A ∨ ¬ A",
+        "Kripke countermodel of the input:",
+        "  worlds: w0, w1",
+        "  order: w0 ≤ w1",
+        "  w0 forces no atoms",
+        "  w1 forces A",
+      ]
+      .join("\n")
+    );
+  }
+
+  #[test]
+  fn test_sequent_calculus() {
+    let options = ExecOptions {
+      system: System::SequentCalculus,
+      ..ExecOptions::default()
+    };
+    let run = |input| exec(input, &options).unwrap_or_else(|err| err.to_string());
+    assert_eq!(
+      run("A to B, A |- B"),
+      "A → B, A ⊢ B [→L]\n+ A → B, A ⊢ A [Ax]\n+ A, B ⊢ B [Ax]\n"
+    );
+    assert!(run("A or not A").starts_with("error when solving:\ncould not infer: A ∨ ¬ A\n"));
+  }
+
+  #[test]
+  fn test_check() {
+    let check = |input, proof| exec_check(input, proof, None).unwrap_or_else(|err| err.to_string());
+    assert_eq!(
+      check("A to B, A |- B", "B [toE]\n+ A\n+ A to B\n"),
+      "valid proof of A → B, A ⊢ B"
+    );
+    assert_eq!(
+      check("A to B, A |- B", "\nB [toE]\n\n+ A\n+ A to B [toI] : 1\n  + A from: 1\n"),
+      "error when checking proof:\nline 5: A → B does not follow from its premises by [→I]"
+    );
+    assert_eq!(
+      check("A to B, A |- B", "B [toE]\n+ A\n"),
+      "error when checking proof:\nline 1: B [→E] takes 2 premise(s)"
+    );
+    assert_eq!(
+      check("A |- A and A", "A"),
+      "error when checking proof:\nline 1: A is not the conclusion A ∧ A"
+    );
+
+    let proof = "¬ ¬ A → A [→I] : 1\n+ A [RAA] : 2\n  + ⊥ [¬E]\n    + ¬ A from: 2\n    + ¬ ¬ A from: 1\n";
+    assert_eq!(
+      check("not not A to A", proof),
+      "error when checking proof:\nline 2: A uses [RAA], which is not enabled"
+    );
+    assert_eq!(
+      exec_check("not not A to A", proof, Some(Classical::Reductio)).unwrap(),
+      "valid proof of ⊢ ¬ ¬ A → A"
+    );
+    assert!(exec_check("not not A to A", proof, Some(Classical::DoubleNegation)).is_err());
+  }
+}
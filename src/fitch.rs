@@ -0,0 +1,363 @@
+//! 証明図をFitch式（旗式）の線形な証明として出力するモジュールです．
+//! 木構造の証明図を帰りがけ順にたどり，仮定を閉じる推論の前提を，その仮定から始まる副証明として書き出します．
+//! 各行には行番号を振り，推論規則と，前提となる行の番号または副証明の範囲を添えます．
+
+use std::sync::Arc;
+
+use super::{logic::*, proof::*, solver::Rule, TeX};
+
+/// Fitch式の証明の1行です．
+struct Line {
+  /// 副証明の深さです．推件の前提と結論は深さ1です．
+  depth: usize,
+
+  /// この行の論理式です．
+  logic: Arc<Logic>,
+
+  /// この行の根拠です．
+  reason: Reason,
+}
+
+/// Fitch式の証明の行の根拠を示す列挙子です．
+enum Reason {
+  /// 推件の前提です．
+  Premise,
+
+  /// 副証明の仮定です．
+  Hypothesis,
+
+  /// 副証明の外にある行の繰り返しです．
+  Reiteration(usize),
+
+  /// 推論規則による推論です．前提となる行と副証明の範囲を持ちます．
+  Rule(Rule, Vec<(usize, usize)>),
+
+  /// 仮定の参照番号が誤っているなど，根拠が見つからない行です．
+  Unknown,
+}
+
+/// 証明図からFitch式の証明の行を組み立てる構造です．
+struct Fitch {
+  /// 組み立てた行です．行番号は添字に1を足したものです．
+  lines: Vec<Line>,
+
+  /// 今いる副証明の仮定の，参照番号，論理式，行番号の組です．
+  scope: Vec<(Option<usize>, Arc<Logic>, usize)>,
+}
+
+/// 前提の行を書き出すのを待っている推論です．
+struct Frame<'a> {
+  /// 書き出している推論です．
+  proof: &'a Proof,
+
+  /// 推論を書き出す深さです．
+  depth: usize,
+
+  /// 書き出した前提の行と副証明の範囲です．
+  cites: Vec<(usize, usize)>,
+
+  /// 書き出している前提が副証明であれば，その仮定の行番号です．
+  start: Option<usize>,
+}
+
+impl Fitch {
+  /// 証明図の推件の前提を，行きがけ順に重複なく最初の行として書き出します．
+  fn premises(&mut self, proof: &Proof) {
+    let mut stack = vec![proof];
+    while let Some(proof) = stack.pop() {
+      if let Step::Premise = proof.step() {
+        if !self.lines.iter().any(|line| &line.logic == proof.conclusion_arc()) {
+          self.push(1, proof.conclusion_arc(), Reason::Premise);
+        }
+      }
+      stack.extend(proof.premises().iter().rev());
+    }
+  }
+
+  /// 行を追加し，その行番号を返します．
+  fn push(&mut self, depth: usize, logic: &Arc<Logic>, reason: Reason) -> usize {
+    self.lines.push(Line {
+      depth,
+      logic: logic.clone(),
+      reason,
+    });
+    self.lines.len()
+  }
+
+  /// 深さ`depth`で推論`proof`を書き出し，その結論を示す行番号を返します．
+  /// 仮定と推件の前提は新たな行を追加せず，既にある行を返します．
+  /// 前提の行を待つ推論は，再帰呼び出しの代わりに[Frame]のスタックに積みます．
+  fn derive(&mut self, proof: &Proof, depth: usize) -> usize {
+    let mut stack = vec![];
+    let mut line = self.leaf(proof, depth, &mut stack);
+    loop {
+      let mut frame = match (line, stack.pop()) {
+        (Some(line), None) => return line,
+        (Some(line), Some(mut frame)) => {
+          match frame.start {
+            Some(start) => {
+              let mut end = line;
+              if end < start {
+                let premise = &frame.proof.premises()[frame.cites.len()];
+                end = self.push(frame.depth + 1, premise.conclusion_arc(), Reason::Reiteration(end));
+              }
+              self.scope.pop();
+              frame.cites.push((start, end));
+            }
+            None => frame.cites.push((line, line)),
+          }
+          frame
+        }
+        (None, Some(frame)) => frame,
+        (None, None) => unreachable!("a pending inference is always on the stack"),
+      };
+
+      // 次の前提を書き出すか，すべて書き出していれば推論の行を書き出します．
+      let (rule, label) = match frame.proof.step() {
+        Step::Inference { rule, label, .. } => (*rule, *label),
+        _ => unreachable!("only inferences wait for their premises"),
+      };
+      let i = frame.cites.len();
+      let premise = match frame.proof.premises().get(i) {
+        Some(premise) => premise,
+        None => {
+          line = Some(self.push(frame.depth, frame.proof.conclusion_arc(), Reason::Rule(rule, frame.cites)));
+          continue;
+        }
+      };
+      frame.start = match frame.proof.hypotheses().swap_remove(i) {
+        Some(hypothesis) => {
+          let hypothesis = Arc::new(hypothesis);
+          let start = self.push(frame.depth + 1, &hypothesis, Reason::Hypothesis);
+          self.scope.push((label, hypothesis, start));
+          Some(start)
+        }
+        None => None,
+      };
+      let depth = frame.depth + usize::from(frame.start.is_some());
+      stack.push(frame);
+      line = self.leaf(premise, depth, &mut stack);
+    }
+  }
+
+  /// 深さ`depth`で推論`proof`を書き出し始めます．仮定と推件の前提であれば，その行番号を返します．
+  /// 推論規則による推論であれば，前提の行を待つ推論としてスタックに積み，[None]を返します．
+  fn leaf<'a>(&mut self, proof: &'a Proof, depth: usize, stack: &mut Vec<Frame<'a>>) -> Option<usize> {
+    let logic = proof.conclusion_arc();
+    match proof.step() {
+      Step::Premise => Some(match self.lines.iter().position(|line| &line.logic == logic) {
+        Some(i) => i + 1,
+        None => self.push(depth, logic, Reason::Unknown),
+      }),
+      Step::Assumption(label) => {
+        let found = self
+          .scope
+          .iter()
+          .rev()
+          .find(|(l, hypothesis, _)| *l == Some(*label) && hypothesis == logic);
+        Some(match found {
+          Some((_, _, line)) => *line,
+          None => self.push(depth, logic, Reason::Unknown),
+        })
+      }
+      Step::Inference { .. } => {
+        stack.push(Frame {
+          proof,
+          depth,
+          cites: vec![],
+          start: None,
+        });
+        None
+      }
+    }
+  }
+
+  /// 証明図`proof`のFitch式の証明の行を組み立てます．
+  fn new(proof: &Proof) -> Self {
+    let mut fitch = Self {
+      lines: vec![],
+      scope: vec![],
+    };
+    fitch.premises(proof);
+    let line = fitch.derive(proof, 1);
+    if line != fitch.lines.len() {
+      fitch.push(1, proof.conclusion_arc(), Reason::Reiteration(line));
+    }
+    fitch
+  }
+
+  /// 行`i`（0から数える）が，推件の前提の最後の行または副証明の仮定であり，その下に横線を引くかを返します．
+  fn is_bar(&self, i: usize) -> bool {
+    match self.lines[i].reason {
+      Reason::Hypothesis => true,
+      Reason::Premise => !matches!(
+        self.lines.get(i + 1),
+        Some(Line {
+          reason: Reason::Premise,
+          ..
+        })
+      ),
+      _ => false,
+    }
+  }
+}
+
+/// 前提となる行と副証明の範囲を，区切り文字`dash`を用いて文字列にします．
+fn cites(cites: &[(usize, usize)], dash: &str) -> String {
+  let cites: Vec<_> = cites
+    .iter()
+    .map(|&(start, end)| {
+      if start == end {
+        start.to_string()
+      } else {
+        format!("{}{}{}", start, dash, end)
+      }
+    })
+    .collect();
+  cites.join(", ")
+}
+
+impl Proof {
+  /// 簡略化した記法でFitch式の証明を出力します．副証明を縦線で，仮定と前提の終わりを横線で示します．
+  pub fn fitch(&self) -> String {
+    let fitch = Fitch::new(self);
+    let number = fitch.lines.len().to_string().len();
+    let logics: Vec<String> = fitch.lines.iter().map(|line| line.logic.to_string()).collect();
+    let widths: Vec<usize> = fitch
+      .lines
+      .iter()
+      .zip(&logics)
+      .map(|(line, logic)| 2 * line.depth + logic.chars().count())
+      .collect();
+    let width = widths.iter().copied().max().unwrap_or(0);
+
+    let mut res = String::new();
+    for (i, line) in fitch.lines.iter().enumerate() {
+      let reason = match line.reason {
+        Reason::Premise | Reason::Hypothesis => String::new(),
+        Reason::Reiteration(line) => format!("R {}", line),
+        Reason::Rule(rule, ref lines) => format!("{} {}", rule, cites(lines, "–")),
+        Reason::Unknown => "?".to_string(),
+      };
+      let reason = reason.trim_end();
+      res.push_str(&format!("{:>number$} {}{}", i + 1, "│ ".repeat(line.depth), logics[i]));
+      if !reason.is_empty() {
+        res.push_str(&" ".repeat(width - widths[i] + 2));
+        res.push_str(reason);
+      }
+      res.push('\n');
+
+      if fitch.is_bar(i) {
+        res.push_str(&format!(
+          "{:>number$} {}├{}\n",
+          "",
+          "│ ".repeat(line.depth - 1),
+          "─".repeat(logics[i].chars().count() + 1)
+        ));
+      }
+    }
+    res
+  }
+
+  /// TeX記法（fitch.sty）でFitch式の証明を出力します．`nd`環境で囲まれます．
+  pub fn fitch_tex(&self) -> String {
+    let fitch = Fitch::new(self);
+    let mut res = String::from("$\n\\begin{nd}\n");
+    let mut depth = 1;
+
+    for (i, line) in fitch.lines.iter().enumerate() {
+      // 隣り合う副証明は，前の副証明を閉じてから開きます．
+      let open = match line.reason {
+        Reason::Hypothesis => line.depth - 1,
+        _ => line.depth,
+      };
+      while depth > open {
+        depth -= 1;
+        res.push_str(&format!("{}\\close\n", "  ".repeat(depth)));
+      }
+      while depth < line.depth {
+        res.push_str(&format!("{}\\open\n", "  ".repeat(depth)));
+        depth += 1;
+      }
+
+      let indent = "  ".repeat(depth);
+      let logic = line.logic.tex();
+      match line.reason {
+        Reason::Premise | Reason::Hypothesis => {
+          res.push_str(&format!("{}\\hypo{{{}}}{{{}}}\n", indent, i + 1, logic))
+        }
+        Reason::Reiteration(cite) => res.push_str(&format!(
+          "{}\\have{{{}}}{{{}}} \\r{{{}}}\n",
+          indent,
+          i + 1,
+          logic,
+          cite
+        )),
+        Reason::Rule(rule, ref lines) => res.push_str(&format!(
+          "{}\\have{{{}}}{{{}}} \\by{{${}$}}{{{}}}\n",
+          indent,
+          i + 1,
+          logic,
+          rule.tex(),
+          cites(lines, "--")
+        )),
+        Reason::Unknown => res.push_str(&format!("{}\\have{{{}}}{{{}}}\n", indent, i + 1, logic)),
+      }
+    }
+    while depth > 1 {
+      depth -= 1;
+      res.push_str(&format!("{}\\close\n", "  ".repeat(depth)));
+    }
+    res.push_str("\\end{nd}\n$\n");
+    res
+  }
+}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+
+  #[test]
+  fn test_fitch() {
+    let logic = Logic::new("A and B to B and A").unwrap();
+    let proof = logic.solve(None).unwrap().to_proof();
+    assert_eq!(
+      proof.fitch(),
+      [
+        "1 │ │ A ∧ B",
+        "  │ ├──────",
+        "2 │ │ B            ∧E 1",
+        "3 │ │ A            ∧E 1",
+        "4 │ │ B ∧ A        ∧I 2, 3",
+        "5 │ A ∧ B → B ∧ A  →I 1–4",
+        "",
+      ]
+      .join("\n")
+    );
+  }
+
+  #[test]
+  fn test_fitch_tex() {
+    let sequent: Sequent = "A |- B to A".parse().unwrap();
+    let proof = sequent.solve(None).unwrap().to_proof();
+    assert_eq!(
+      proof.fitch_tex(),
+      [
+        "$",
+        "\\begin{nd}",
+        "  \\hypo{1}{A}",
+        "  \\open",
+        "    \\hypo{2}{B}",
+        "    \\have{3}{A} \\r{1}",
+        "  \\close",
+        "  \\have{4}{B \\to A} \\by{$\\to I$}{2--3}",
+        "\\end{nd}",
+        "$",
+        "",
+      ]
+      .join("\n")
+    );
+  }
+}
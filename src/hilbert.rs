@@ -0,0 +1,658 @@
+//! 自然演繹の証明図を，ヒルベルト流の公理系による線形な導出に翻訳するモジュールです．
+//! 公理は論理包含の公理K，Sと，各論理結合子の公理図式で，推論規則はモーダスポネンスのみです．
+//!
+//! 仮定を閉じる推論は，その前提の導出に演繹定理を適用して`A → B`の形に直し，対応する公理図式と組み合わせます．
+//! 同じ論理式の行は，依存する仮定が少ない行に限って使い回すため，出力は証明図の大きさに比べて大きくなりすぎません．
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::{arena::*, checker::ProofError, logic::*, proof::*, solver::Rule, TeX};
+
+/// ヒルベルト流の公理系の公理図式を示す列挙子です．`A`，`B`，`C`は任意の論理式です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axiom {
+  /// `A → (B → A)`です．
+  K,
+
+  /// `(A → (B → C)) → ((A → B) → (A → C))`です．
+  S,
+
+  /// `A → (B → A ∧ B)`です．
+  AndIntro,
+
+  /// `A ∧ B → A`です．
+  AndElimLeft,
+
+  /// `A ∧ B → B`です．
+  AndElimRight,
+
+  /// `A → A ∨ B`です．
+  OrIntroLeft,
+
+  /// `B → A ∨ B`です．
+  OrIntroRight,
+
+  /// `(A → C) → ((B → C) → (A ∨ B → C))`です．
+  OrElim,
+
+  /// `(A → ⊥) → ¬A`です．
+  NotIntro,
+
+  /// `¬A → (A → ⊥)`です．
+  NotElim,
+
+  /// `(A → B) → ((B → A) → (A ↔ B))`です．
+  IffIntro,
+
+  /// `(A ↔ B) → (A → B)`です．
+  IffElimLeft,
+
+  /// `(A ↔ B) → (B → A)`です．
+  IffElimRight,
+
+  /// `⊥ → A`です．
+  ContElim,
+
+  /// 古典論理の公理`A ∨ ¬A`です．
+  ExcludedMiddle,
+
+  /// 古典論理の公理`¬¬A → A`です．
+  DoubleNegation,
+
+  /// 古典論理の公理`(¬A → ⊥) → A`です．
+  Reductio,
+}
+
+impl TeX for Axiom {
+  fn tex(&self) -> String {
+    match self {
+      Self::K => "\\mathrm{K}",
+      Self::S => "\\mathrm{S}",
+      Self::AndIntro => "\\land I",
+      Self::AndElimLeft => "\\land E_1",
+      Self::AndElimRight => "\\land E_2",
+      Self::OrIntroLeft => "\\lor I_1",
+      Self::OrIntroRight => "\\lor I_2",
+      Self::OrElim => "\\lor E",
+      Self::NotIntro => "\\lnot I",
+      Self::NotElim => "\\lnot E",
+      Self::IffIntro => "\\leftrightarrow I",
+      Self::IffElimLeft => "\\leftrightarrow E_1",
+      Self::IffElimRight => "\\leftrightarrow E_2",
+      Self::ContElim => "\\perp E",
+      Self::ExcludedMiddle => "\\mathrm{LEM}",
+      Self::DoubleNegation => "\\mathrm{DNE}",
+      Self::Reductio => "\\mathrm{RAA}",
+    }
+    .to_string()
+  }
+}
+
+impl Display for Axiom {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let name = match self {
+      Self::K => "K",
+      Self::S => "S",
+      Self::AndIntro => "∧I",
+      Self::AndElimLeft => "∧E₁",
+      Self::AndElimRight => "∧E₂",
+      Self::OrIntroLeft => "∨I₁",
+      Self::OrIntroRight => "∨I₂",
+      Self::OrElim => "∨E",
+      Self::NotIntro => "¬I",
+      Self::NotElim => "¬E",
+      Self::IffIntro => "↔I",
+      Self::IffElimLeft => "↔E₁",
+      Self::IffElimRight => "↔E₂",
+      Self::ContElim => "⊥E",
+      Self::ExcludedMiddle => "LEM",
+      Self::DoubleNegation => "DNE",
+      Self::Reductio => "RAA",
+    };
+    write!(f, "{}", name)
+  }
+}
+
+/// ヒルベルト流の導出の行の根拠を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Justification {
+  /// 推件の前提です．
+  Premise,
+
+  /// 公理図式の実例です．
+  Axiom(Axiom),
+
+  /// モーダスポネンスです．`A`の行と`A → B`の行の行番号（1から数える）を持ちます．
+  ModusPonens(usize, usize),
+}
+
+/// ヒルベルト流の導出です．各行は前提，公理，またはそれより上の行からのモーダスポネンスです．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hilbert {
+  /// 導出の行です．最後の行が結論です．
+  lines: Vec<(Logic, Justification)>,
+}
+
+/// 翻訳中の導出の行の根拠です．まだ閉じられていない仮定を含みます．
+#[derive(Clone, Copy)]
+enum Reason {
+  /// 閉じられていない仮定です．
+  Hypothesis,
+
+  /// 翻訳後の導出と同じ根拠です．行番号は0から数えます．
+  Justified(Justification),
+}
+
+/// 翻訳中の導出の行です．
+struct Line {
+  /// この行の論理式です．
+  logic: FormulaId,
+
+  /// この行の根拠です．
+  reason: Reason,
+
+  /// この行が依存する，閉じられていない仮定です．
+  hypotheses: Vec<FormulaId>,
+}
+
+/// 証明図を翻訳する構造です．すべての行を1つの列に積み，仮定に依存しない範囲で行を使い回します．
+/// 論理式はアリーナに格納し，比較と索引を木の大きさによらず行います．
+struct Translator {
+  /// 論理式のアリーナです．
+  arena: Arena,
+
+  /// 積まれた行です．
+  lines: Vec<Line>,
+
+  /// 論理式ごとの，その論理式の行の行番号です．
+  index: HashMap<FormulaId, Vec<usize>>,
+
+  /// 演繹定理で得た行の，元の行と閉じた仮定の組による索引です．
+  discharged: HashMap<(usize, FormulaId), usize>,
+}
+
+impl Translator {
+  /// `left → right`の論理式を格納し，その番号を返します．
+  fn to(&mut self, left: FormulaId, right: FormulaId) -> FormulaId {
+    self.arena.intern(Formula::To(left, right))
+  }
+
+  /// 行を積み，その行番号を返します．同じ論理式の行が既にあり，それが依存する仮定がこの行の仮定に含まれれば，その行を返します．
+  fn push(&mut self, logic: FormulaId, reason: Reason) -> usize {
+    let hypotheses = match reason {
+      Reason::Hypothesis => vec![logic],
+      Reason::Justified(Justification::ModusPonens(minor, major)) => {
+        let mut hypotheses = self.lines[minor].hypotheses.clone();
+        for &hypothesis in &self.lines[major].hypotheses {
+          if !hypotheses.contains(&hypothesis) {
+            hypotheses.push(hypothesis);
+          }
+        }
+        hypotheses
+      }
+      Reason::Justified(_) => vec![],
+    };
+
+    let all = &self.lines;
+    let lines = self.index.entry(logic).or_default();
+    let found = lines
+      .iter()
+      .copied()
+      .find(|&i| all[i].hypotheses.iter().all(|h| hypotheses.contains(h)));
+    if let Some(i) = found {
+      return i;
+    }
+
+    lines.push(self.lines.len());
+    self.lines.push(Line {
+      logic,
+      reason,
+      hypotheses,
+    });
+    self.lines.len() - 1
+  }
+
+  /// 公理図式`axiom`の実例`logic`を積みます．
+  fn axiom(&mut self, logic: FormulaId, axiom: Axiom) -> usize {
+    self.push(logic, Reason::Justified(Justification::Axiom(axiom)))
+  }
+
+  /// `A`の行`minor`と`A → B`の行`major`から，モーダスポネンスで`B`を積みます．
+  fn mp(&mut self, minor: usize, major: usize) -> usize {
+    let logic = match self.arena[self.lines[major].logic] {
+      Formula::To(_, right) => right,
+      _ => unreachable!("the major premise of modus ponens must be an implication"),
+    };
+    self.push(
+      logic,
+      Reason::Justified(Justification::ModusPonens(minor, major)),
+    )
+  }
+
+  /// 公理図式`axiom`の実例`A → B`と`A`の行`minor`から，`B`を積みます．
+  fn apply(&mut self, minor: usize, logic: FormulaId, axiom: Axiom) -> usize {
+    let major = self.axiom(logic, axiom);
+    self.mp(minor, major)
+  }
+
+  /// 演繹定理により，行`i`の論理式`B`から仮定`h`を閉じた`h → B`の行を積みます．
+  /// モーダスポネンスの行では両方の前提の行から先に仮定を閉じるため，その行を明示的なスタックに積みます．
+  fn discharge(&mut self, i: usize, h: FormulaId) -> usize {
+    let mut stack = vec![(i, false)];
+    while let Some((i, visited)) = stack.pop() {
+      if self.discharged.contains_key(&(i, h)) {
+        continue;
+      }
+
+      let logic = self.lines[i].logic;
+      let res = if !self.lines[i].hypotheses.contains(&h) {
+        let hl = self.to(h, logic);
+        let k = self.to(logic, hl);
+        self.apply(i, k, Axiom::K)
+      } else if logic == h {
+        // A → A をK，Sから導きます．
+        let hh = self.to(h, h);
+        let hhh = self.to(hh, h);
+        let h_hhh = self.to(h, hhh);
+        let h_hh = self.to(h, hh);
+        let h_hh_hh = self.to(h_hh, hh);
+        let s = self.to(h_hhh, h_hh_hh);
+        let s = self.axiom(s, Axiom::S);
+        let k1 = self.axiom(h_hhh, Axiom::K);
+        let t = self.mp(k1, s);
+        let k2 = self.axiom(h_hh, Axiom::K);
+        self.mp(k2, t)
+      } else {
+        let (minor, major) = match self.lines[i].reason {
+          Reason::Justified(Justification::ModusPonens(minor, major)) => (minor, major),
+          _ => unreachable!("only modus ponens depends on a hypothesis other than itself"),
+        };
+        if !visited {
+          stack.push((i, true));
+          stack.push((major, false));
+          stack.push((minor, false));
+          continue;
+        }
+
+        let antecedent = self.lines[minor].logic;
+        let hminor = self.discharged[&(minor, h)];
+        let hmajor = self.discharged[&(major, h)];
+        let al = self.to(antecedent, logic);
+        let h_al = self.to(h, al);
+        let ha = self.to(h, antecedent);
+        let hl = self.to(h, logic);
+        let ha_hl = self.to(ha, hl);
+        let s = self.to(h_al, ha_hl);
+        let s = self.axiom(s, Axiom::S);
+        let t = self.mp(hmajor, s);
+        self.mp(hminor, t)
+      };
+      self.discharged.insert((i, h), res);
+    }
+    self.discharged[&(i, h)]
+  }
+
+  /// 証明図`proof`を翻訳し，その結論の行番号を返します．証明図は推論規則に従っている必要があります．
+  /// 前提を翻訳してから推論を翻訳するため，推論を明示的なスタックに積んで帰りがけ順にたどります．
+  fn translate(&mut self, proof: &Proof) -> usize {
+    let mut stack = vec![(proof, false)];
+    let mut lines = vec![];
+    while let Some((proof, visited)) = stack.pop() {
+      if let (Step::Inference { .. }, false) = (proof.step(), visited) {
+        stack.push((proof, true));
+        stack.extend(proof.premises().iter().rev().map(|premise| (premise, false)));
+        continue;
+      }
+
+      let conclusion = self.arena.insert(proof.conclusion());
+      let line = match proof.step() {
+        Step::Premise => self.push(conclusion, Reason::Justified(Justification::Premise)),
+        Step::Assumption(_) => self.push(conclusion, Reason::Hypothesis),
+        Step::Inference { rule, premises, .. } => {
+          let premises = lines.split_off(lines.len() - premises.len());
+          self.infer(*rule, conclusion, &premises)
+        }
+      };
+      lines.push(line);
+    }
+    lines.pop().unwrap()
+  }
+
+  /// 前提の行`premises`から，推論規則`rule`で`conclusion`を推論する行を積みます．
+  fn infer(&mut self, rule: Rule, conclusion: FormulaId, premises: &[usize]) -> usize {
+    let c = conclusion;
+    let logics: Vec<FormulaId> = premises.iter().map(|&i| self.lines[i].logic).collect();
+    let nodes: Vec<Formula> = logics.iter().map(|&logic| self.arena[logic].clone()).collect();
+    let cont = self.arena.intern(Formula::Cont);
+    match (rule, self.arena[c].clone(), nodes.as_slice()) {
+      (Rule::ToIntro, Formula::To(a, _), _) => self.discharge(premises[0], a),
+      (Rule::ToElim, _, _) => self.mp(premises[0], premises[1]),
+      (Rule::NotIntro, Formula::Not(a), _) => {
+        let d = self.discharge(premises[0], a);
+        let a_cont = self.to(a, cont);
+        let axiom = self.to(a_cont, c);
+        self.apply(d, axiom, Axiom::NotIntro)
+      }
+      (Rule::NotElim, _, [_, _]) => {
+        let (a, not) = (logics[0], logics[1]);
+        let a_cont = self.to(a, cont);
+        let axiom = self.to(not, a_cont);
+        let t = self.apply(premises[1], axiom, Axiom::NotElim);
+        self.mp(premises[0], t)
+      }
+      (Rule::AndIntro, Formula::And(a, b), _) => {
+        let bc = self.to(b, c);
+        let axiom = self.to(a, bc);
+        let t = self.apply(premises[0], axiom, Axiom::AndIntro);
+        self.mp(premises[1], t)
+      }
+      (Rule::AndElim, _, [Formula::And(a, _)]) => {
+        let axiom = if c == *a {
+          Axiom::AndElimLeft
+        } else {
+          Axiom::AndElimRight
+        };
+        let logic = self.to(logics[0], c);
+        self.apply(premises[0], logic, axiom)
+      }
+      (Rule::OrIntro, Formula::Or(a, _), [_]) => {
+        let axiom = if a == logics[0] {
+          Axiom::OrIntroLeft
+        } else {
+          Axiom::OrIntroRight
+        };
+        let logic = self.to(logics[0], c);
+        self.apply(premises[0], logic, axiom)
+      }
+      (Rule::OrElim, _, [Formula::Or(a, b), _, _]) => {
+        let (a, b, or) = (*a, *b, logics[0]);
+        let left = self.discharge(premises[1], a);
+        let right = self.discharge(premises[2], b);
+        let ac = self.to(a, c);
+        let bc = self.to(b, c);
+        let or_c = self.to(or, c);
+        let bc_or_c = self.to(bc, or_c);
+        let axiom = self.to(ac, bc_or_c);
+        let t = self.apply(left, axiom, Axiom::OrElim);
+        let t = self.mp(right, t);
+        self.mp(premises[0], t)
+      }
+      (Rule::IffIntro, Formula::Iff(a, b), _) => {
+        let left = self.discharge(premises[0], a);
+        let right = self.discharge(premises[1], b);
+        let ab = self.to(a, b);
+        let ba = self.to(b, a);
+        let ba_c = self.to(ba, c);
+        let axiom = self.to(ab, ba_c);
+        let t = self.apply(left, axiom, Axiom::IffIntro);
+        self.mp(right, t)
+      }
+      (Rule::IffElim, _, [_, Formula::Iff(a, _)]) => {
+        let (d, iff) = (logics[0], logics[1]);
+        let axiom = if d == *a {
+          Axiom::IffElimLeft
+        } else {
+          Axiom::IffElimRight
+        };
+        let dc = self.to(d, c);
+        let logic = self.to(iff, dc);
+        let t = self.apply(premises[1], logic, axiom);
+        self.mp(premises[0], t)
+      }
+      (Rule::ContElim, _, _) => {
+        let logic = self.to(cont, c);
+        self.apply(premises[0], logic, Axiom::ContElim)
+      }
+      (Rule::ExcludedMiddle, _, _) => self.axiom(c, Axiom::ExcludedMiddle),
+      (Rule::DoubleNegation, _, [_]) => {
+        let logic = self.to(logics[0], c);
+        self.apply(premises[0], logic, Axiom::DoubleNegation)
+      }
+      (Rule::Reductio, _, _) => {
+        let not = self.arena.intern(Formula::Not(c));
+        let d = self.discharge(premises[0], not);
+        let not_cont = self.to(not, cont);
+        let logic = self.to(not_cont, c);
+        self.apply(d, logic, Axiom::Reductio)
+      }
+      _ => unreachable!("the proof has been checked"),
+    }
+  }
+
+  /// 行`root`の導出に用いる行のみを，上から順に取り出します．
+  fn finish(self, root: usize) -> Hilbert {
+    let mut used = vec![false; self.lines.len()];
+    used[root] = true;
+    for i in (0..=root).rev() {
+      if let (true, Reason::Justified(Justification::ModusPonens(minor, major))) =
+        (used[i], self.lines[i].reason)
+      {
+        used[minor] = true;
+        used[major] = true;
+      }
+    }
+
+    let mut numbers = vec![0; self.lines.len()];
+    let mut lines = vec![];
+    for (i, line) in self.lines.iter().enumerate().take(root + 1) {
+      if !used[i] {
+        continue;
+      }
+      let justification = match line.reason {
+        Reason::Justified(Justification::ModusPonens(minor, major)) => {
+          Justification::ModusPonens(numbers[minor], numbers[major])
+        }
+        Reason::Justified(justification) => justification,
+        Reason::Hypothesis => unreachable!("every hypothesis has been discharged"),
+      };
+      lines.push((self.arena.logic(line.logic), justification));
+      numbers[i] = lines.len();
+    }
+    Hilbert { lines }
+  }
+}
+
+impl Proof {
+  /// 証明図をヒルベルト流の導出に翻訳します．閉じられない仮定は推件の前提として扱います．
+  /// 古典論理の規則は，証明図で最初に現れる1種類だけを用いることができます．
+  /// 証明図が推論規則に従っていなければ，その誤りを[ProofError]として返します．
+  pub fn hilbert(&self) -> Result<Hilbert, ProofError> {
+    let mut logics = vec![];
+    let mut classical = None;
+    let mut stack = vec![self];
+    while let Some(proof) = stack.pop() {
+      if let Step::Premise = proof.step() {
+        logics.push(proof.conclusion().clone());
+      }
+      classical = classical.or_else(|| proof.rule().and_then(|rule| rule.classical()));
+      stack.extend(proof.premises().iter().rev());
+    }
+    self.check(&logics, classical)?;
+
+    let mut translator = Translator {
+      arena: Arena::new(),
+      lines: vec![],
+      index: HashMap::new(),
+      discharged: HashMap::new(),
+    };
+    let root = translator.translate(self);
+    Ok(translator.finish(root))
+  }
+}
+
+impl Hilbert {
+  /// 導出の行を，論理式とその根拠の組として上から順に返します．最後の行が結論です．
+  pub fn lines(&self) -> &[(Logic, Justification)] {
+    &self.lines
+  }
+
+  /// 行の根拠を文字列にします．`tex`が`true`であれば公理の名前をTeX記法にします．
+  fn justification(justification: &Justification, tex: bool) -> String {
+    match justification {
+      Justification::Premise => "premise".to_string(),
+      Justification::Axiom(axiom) if tex => format!("${}$", axiom.tex()),
+      Justification::Axiom(axiom) => axiom.to_string(),
+      Justification::ModusPonens(minor, major) => format!("MP {}, {}", minor, major),
+    }
+  }
+}
+
+impl TeX for Hilbert {
+  /// TeXの`tabular`環境を出力します．行番号，論理式，根拠の3列です．
+  fn tex(&self) -> String {
+    let mut table = String::from("\\begin{tabular}{rll}\n");
+    for (i, (logic, justification)) in self.lines.iter().enumerate() {
+      table.push_str(&format!(
+        "  {} & ${}$ & {} \\\\\n",
+        i + 1,
+        logic.tex(),
+        Self::justification(justification, true)
+      ));
+    }
+    table.push_str("\\end{tabular}\n");
+    table
+  }
+}
+
+impl Display for Hilbert {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let number = self.lines.len().to_string().len();
+    let logics: Vec<String> = self.lines.iter().map(|(logic, _)| logic.to_string()).collect();
+    let width = logics.iter().map(|s| s.chars().count()).max().unwrap_or(0);
+
+    for (i, ((_, justification), logic)) in self.lines.iter().zip(&logics).enumerate() {
+      let padding = " ".repeat(width - logic.chars().count());
+      writeln!(
+        f,
+        "{:>number$}  {}{}  {}",
+        i + 1,
+        logic,
+        padding,
+        Self::justification(justification, false)
+      )?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+  use crate::solver::Classical;
+
+  /// 論理式`logic`が，原子式を任意の論理式とみなした図式`schema`の実例であるかを返します．
+  /// `map`には，既に対応が決まった原子式とその論理式を持ちます．
+  fn instance(schema: &Logic, logic: &Logic, map: &mut HashMap<String, Logic>) -> bool {
+    use Logic::*;
+    match (schema, logic) {
+      (Base(name), _) => map.entry(name.clone()).or_insert_with(|| logic.clone()) == logic,
+      (Cont, Cont) => true,
+      (Not(s), Not(l)) => instance(s, l, map),
+      (And(s0, s1), And(l0, l1))
+      | (Or(s0, s1), Or(l0, l1))
+      | (To(s0, s1), To(l0, l1))
+      | (Iff(s0, s1), Iff(l0, l1)) => instance(s0, l0, map) && instance(s1, l1, map),
+      _ => false,
+    }
+  }
+
+  /// 公理図式を，原子式`A`，`B`，`C`を任意の論理式とみなした論理式として返します．
+  fn schema(axiom: Axiom) -> Logic {
+    let s = match axiom {
+      Axiom::K => "A to B to A",
+      Axiom::S => "(A to B to C) to (A to B) to A to C",
+      Axiom::AndIntro => "A to B to A and B",
+      Axiom::AndElimLeft => "A and B to A",
+      Axiom::AndElimRight => "A and B to B",
+      Axiom::OrIntroLeft => "A to A or B",
+      Axiom::OrIntroRight => "B to A or B",
+      Axiom::OrElim => "(A to C) to (B to C) to A or B to C",
+      Axiom::NotIntro => "(A to cont) to not A",
+      Axiom::NotElim => "not A to A to cont",
+      Axiom::IffIntro => "(A to B) to (B to A) to (A iff B)",
+      Axiom::IffElimLeft => "(A iff B) to A to B",
+      Axiom::IffElimRight => "(A iff B) to B to A",
+      Axiom::ContElim => "cont to A",
+      Axiom::ExcludedMiddle => "A or not A",
+      Axiom::DoubleNegation => "not not A to A",
+      Axiom::Reductio => "(not A to cont) to A",
+    };
+    Logic::new(s).unwrap()
+  }
+
+  /// 導出の各行が，前提，公理図式の実例，上の行からのモーダスポネンスのいずれかであることを確かめます．
+  fn verify(hilbert: &Hilbert, premises: &[Logic]) {
+    let b = |logic: &Logic| Box::new(logic.clone());
+    for (i, (logic, justification)) in hilbert.lines().iter().enumerate() {
+      let valid = match justification {
+        Justification::Premise => premises.contains(logic),
+        Justification::ModusPonens(minor, major) => {
+          *minor <= i
+            && *major <= i
+            && hilbert.lines()[major - 1].0 == Logic::To(b(&hilbert.lines()[minor - 1].0), b(logic))
+        }
+        Justification::Axiom(axiom) => instance(&schema(*axiom), logic, &mut HashMap::new()),
+      };
+      assert!(valid, "line {}: {}", i + 1, logic);
+    }
+  }
+
+  #[test]
+  fn test_schema() {
+    let and = Logic::new("(P or Q) and R to P or Q").unwrap();
+    assert!(instance(&schema(Axiom::AndElimLeft), &and, &mut HashMap::new()));
+    assert!(!instance(&schema(Axiom::AndElimRight), &and, &mut HashMap::new()));
+    let k = Logic::new("P to Q to Q").unwrap();
+    assert!(!instance(&schema(Axiom::K), &k, &mut HashMap::new()));
+  }
+
+  #[test]
+  fn test_hilbert() {
+    let logic = Logic::new("A to A").unwrap();
+    let hilbert = logic.solve(None).unwrap().to_proof().hilbert().unwrap();
+    assert_eq!(
+      hilbert.to_string(),
+      [
+        "1  (A → (A → A) → A) → (A → A → A) → A → A  S",
+        "2  A → (A → A) → A                          K",
+        "3  (A → A → A) → A → A                      MP 2, 1",
+        "4  A → A → A                                K",
+        "5  A → A                                    MP 4, 3",
+        "",
+      ]
+      .join("\n")
+    );
+
+    for s in [
+      "((A or B) to C) to (A to C) and (B to C)",
+      "(A iff B) and (B iff C) to (A iff C)",
+      "not not (A or not A)",
+      "A to B to A",
+      "A and not A to B",
+      "(A or B) and not A to B",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      let hilbert = logic.solve(None).unwrap().to_proof().hilbert().unwrap();
+      verify(&hilbert, &[]);
+      assert_eq!(hilbert.lines().last().unwrap().0, logic, "{}", s);
+    }
+
+    let logic = Logic::new("((A to B) to A) to A").unwrap();
+    for classical in [
+      Classical::ExcludedMiddle,
+      Classical::DoubleNegation,
+      Classical::Reductio,
+    ] {
+      let hilbert = logic.solve(Some(classical)).unwrap().to_proof().hilbert().unwrap();
+      verify(&hilbert, &[]);
+    }
+
+    let sequent: Sequent = "A to B, B to C |- A to C".parse().unwrap();
+    let hilbert = sequent.solve(None).unwrap().to_proof().hilbert().unwrap();
+    verify(&hilbert, &sequent.premises);
+    assert_eq!(hilbert.lines().last().unwrap().0, sequent.conclusion);
+  }
+}
@@ -0,0 +1,690 @@
+//! 直観主義論理上証明不可能な推件に対し，反例となる有限のクリプキモデルを構成するモジュールです．
+//! 各世界で強制される論理式と強制されない論理式を記号付きタブローで分解し，
+//! 強制されない論理包含と否定ごとに，その前件を強制する後続の世界を作ります．
+//! 後続の世界は強制される論理式をすべて引き継ぐため，モデルは木になり，原子式の強制は単調になります．
+//!
+//! 世界で強制される論理式は部分式に限られ，後続の世界では必ず増えるため，探索は停止します．
+//! 得られたモデルからは，反例であることを保ったまま取り除ける世界を取り除きます．
+//! 探索は再帰呼び出しの代わりにスタックを用い，証明探索と同じ[Budget]で資源を制限します．
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::vec::IntoIter;
+
+use super::{arena::*, budget::*, logic::*, TeX};
+
+/// クリプキモデルです．世界0を根とする木で，到達可能性の順序は後続の関係の反射推移閉包です．
+/// 世界は行きがけ順に番号が振られるため，後続の世界の番号は必ず大きくなります．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kripke {
+  /// 各世界で強制される原子式の名前です．名前順に並んでいます．
+  worlds: Vec<Vec<String>>,
+
+  /// 各世界の直後の世界です．
+  successors: Vec<Vec<usize>>,
+}
+
+/// クリプキモデルの出力形式を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFormat {
+  /// 世界，順序，強制される原子式を並べた記法です．
+  Plain,
+
+  /// Graphvizの`digraph`です．根を下に描きます．
+  Graphviz,
+
+  /// TikZの`tikzpicture`環境です．根を下に描きます．
+  Tikz,
+}
+
+impl FromStr for ModelFormat {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "plain" => Ok(Self::Plain),
+      "graphviz" => Ok(Self::Graphviz),
+      "tikz" => Ok(Self::Tikz),
+      _ => Err(format!(
+        "unknown model format: {} (expected plain, graphviz or tikz)",
+        s
+      )),
+    }
+  }
+}
+
+
+/// 反例を探索する世界の状態です．この世界で強制されるべき論理式と，強制されてはならない論理式を持ちます．
+#[derive(Debug, Clone, Default)]
+struct World {
+  /// 強制されるべき論理式です．後続の世界に引き継がれます．
+  forced: BTreeSet<FormulaId>,
+
+  /// 強制されてはならない論理式です．
+  unforced: BTreeSet<FormulaId>,
+
+  /// 加えたがまだ分解していない論理式です．`true`であれば強制されるべき論理式です．
+  pending: Vec<(bool, FormulaId)>,
+}
+
+/// 世界の状態を局所的に分解した結果を示す列挙子です．
+enum Step {
+  /// 矛盾しました．この状態を根とする反例はありません．
+  Closed,
+
+  /// 2つの状態に分かれました．どちらかを根とする反例があれば，それが反例です．
+  Branch(World, World),
+
+  /// 矛盾なく分解し尽くしました．分解し尽くした状態を持ちます．
+  Open(World),
+}
+
+/// 反例の探索で次に行うことを示す列挙子です．
+enum Next {
+  /// 世界の状態を根とする反例を探します．
+  Refute(World),
+
+  /// 直前に探した反例が見つかったかを，スタックの上の探索に返します．
+  Return(bool),
+}
+
+/// 部分の探索の結果を待っている探索を示す列挙子です．
+enum Frame {
+  /// 探索の深さを1つ深くしています．結果を受け取ると深さを戻し，そのまま返します．
+  Leave(Depth),
+
+  /// 状態が分かれた一方を探索しています．失敗すれば，モデルを世界`usize`個まで戻して他方を探索します．
+  Or(World, usize),
+
+  /// 世界`world`の後続の世界を順に探索しています．`start`は探索している後続の世界の番号です．
+  Children {
+    world: usize,
+    start: usize,
+    rest: IntoIter<World>,
+  },
+}
+
+impl World {
+  /// `logic`を強制されるべき論理式に加えます．
+  fn force(&mut self, logic: FormulaId) {
+    if self.forced.insert(logic) {
+      self.pending.push((true, logic));
+    }
+  }
+
+  /// `logic`を強制されてはならない論理式に加えます．
+  fn unforce(&mut self, logic: FormulaId) {
+    if self.unforced.insert(logic) {
+      self.pending.push((false, logic));
+    }
+  }
+
+  /// 自分を複製し，それぞれに`left`と`right`で論理式を加えた2つの状態に分けます．
+  fn branch(self, left: impl FnOnce(&mut Self), right: impl FnOnce(&mut Self)) -> Step {
+    let mut other = self.clone();
+    let mut world = self;
+    left(&mut world);
+    right(&mut other);
+    Step::Branch(world, other)
+  }
+
+  /// 加えた論理式を，矛盾するか分岐するまで分解します．
+  fn saturate(mut self, arena: &mut Arena) -> Step {
+    loop {
+      while let Some((forced, logic)) = self.pending.pop() {
+        let has = |world: &Self, logic| world.forced.contains(&logic);
+        let lacks = |world: &Self, logic| world.unforced.contains(&logic);
+        if forced {
+          if lacks(&self, logic) {
+            return Step::Closed;
+          }
+          match arena[logic] {
+            Formula::Cont => return Step::Closed,
+            Formula::Not(left) => self.unforce(left),
+            Formula::And(left, right) => {
+              self.force(left);
+              self.force(right);
+            }
+            Formula::Or(left, right) if !has(&self, left) && !has(&self, right) => {
+              return self.branch(|w| w.force(left), |w| w.force(right))
+            }
+            Formula::To(left, right) if !lacks(&self, left) && !has(&self, right) => {
+              return self.branch(|w| w.force(right), |w| w.unforce(left))
+            }
+            Formula::Iff(left, right)
+              if !(has(&self, left) && has(&self, right) || lacks(&self, left) && lacks(&self, right)) =>
+            {
+              return self.branch(
+                |w| {
+                  w.force(left);
+                  w.force(right)
+                },
+                |w| {
+                  w.unforce(left);
+                  w.unforce(right)
+                },
+              )
+            }
+            _ => (),
+          }
+        } else {
+          if has(&self, logic) {
+            return Step::Closed;
+          }
+          match arena[logic] {
+            Formula::And(left, right) if !lacks(&self, left) && !lacks(&self, right) => {
+              return self.branch(|w| w.unforce(left), |w| w.unforce(right))
+            }
+            Formula::Or(left, right) => {
+              self.unforce(left);
+              self.unforce(right);
+            }
+            Formula::To(left, right) if has(&self, left) => self.unforce(right),
+            Formula::Iff(left, right) => {
+              let to = arena.intern(Formula::To(left, right));
+              let from = arena.intern(Formula::To(right, left));
+              if !lacks(&self, to) && !lacks(&self, from) {
+                return self.branch(|w| w.unforce(to), |w| w.unforce(from));
+              }
+            }
+            _ => (),
+          }
+        }
+      }
+
+      // 前件が後から強制された論理包含は，この世界で後件を強制しないことで強制しません．
+      let rights: Vec<FormulaId> = self
+        .unforced
+        .iter()
+        .filter_map(|&logic| match arena[logic] {
+          Formula::To(left, right) if self.forced.contains(&left) && !self.unforced.contains(&right) => {
+            Some(right)
+          }
+          _ => None,
+        })
+        .collect();
+      if rights.is_empty() {
+        return Step::Open(self);
+      }
+      for right in rights {
+        self.unforce(right);
+      }
+    }
+  }
+
+  /// 強制されない論理包含と否定それぞれについて，その前件を強制する後続の世界の状態を返します．
+  fn successors(&self, arena: &Arena) -> Vec<Self> {
+    let mut res = vec![];
+    for &logic in &self.unforced {
+      let (left, right) = match arena[logic] {
+        Formula::Not(left) if !self.forced.contains(&left) => (left, None),
+        Formula::To(left, right) if !self.forced.contains(&left) => (left, Some(right)),
+        _ => continue,
+      };
+      let mut world = Self {
+        forced: self.forced.clone(),
+        unforced: BTreeSet::new(),
+        pending: self.forced.iter().map(|&logic| (true, logic)).collect(),
+      };
+      world.force(left);
+      if let Some(right) = right {
+        world.unforce(right);
+      }
+      res.push(world);
+    }
+    res
+  }
+
+  /// 強制される原子式の名前を，名前順に返します．
+  fn atoms(&self, arena: &Arena) -> Vec<String> {
+    let atoms: BTreeSet<String> = self
+      .forced
+      .iter()
+      .filter_map(|&logic| match arena[logic] {
+        Formula::Base(ref name) => Some(name.clone()),
+        _ => None,
+      })
+      .collect();
+    atoms.into_iter().collect()
+  }
+}
+
+impl Kripke {
+  /// 前提`premises`をすべて強制し，結論`conclusion`を強制しない世界を根とするクリプキモデルを探します．
+  /// 推件が直観主義論理上証明可能であれば，そのようなモデルは存在せず[None]を返します．
+  /// 探索した世界の状態と，取り除けるか確かめたモデルを`budget`で数え，制限に達すればその制限を返します．
+  pub fn find(premises: &[Logic], conclusion: &Logic, budget: &Rc<Budget>) -> Result<Option<Self>, Resource> {
+    let mut arena = Arena::new();
+    let premises: Vec<FormulaId> = premises.iter().map(|premise| arena.insert(premise)).collect();
+    let conclusion = arena.insert(conclusion);
+
+    let mut world = World::default();
+    for &premise in &premises {
+      world.force(premise);
+    }
+    world.unforce(conclusion);
+    let mut model = match Self::refute(world, &mut arena, budget)? {
+      Some(model) => model,
+      None => return Ok(None),
+    };
+
+    // 反例であるうちは，行きがけ順に最初に取り除ける世界を取り除きます．
+    'remove: loop {
+      for world in 0..model.len() {
+        if let Some(smaller) = model.without(world) {
+          budget.enter()?;
+          if smaller.refutes(&arena, &premises, conclusion) {
+            model = smaller;
+            continue 'remove;
+          }
+        }
+      }
+      return Ok(Some(model));
+    }
+  }
+
+  /// 状態`world`を根とする反例を探します．見つからなければ[None]を返します．
+  /// 部分の探索を待つ探索は，再帰呼び出しの代わりに[Frame]のスタックに積み，世界は見つけた順にモデルに加えます．
+  fn refute(world: World, arena: &mut Arena, budget: &Rc<Budget>) -> Result<Option<Self>, Resource> {
+    let mut model = Self {
+      worlds: vec![],
+      successors: vec![],
+    };
+    let mut stack = vec![];
+    let mut next = Next::Refute(world);
+    loop {
+      next = match next {
+        Next::Refute(world) => {
+          stack.push(Frame::Leave(budget.enter()?));
+          match world.saturate(arena) {
+            Step::Closed => Next::Return(false),
+            Step::Branch(world, other) => {
+              stack.push(Frame::Or(other, model.len()));
+              Next::Refute(world)
+            }
+            Step::Open(world) => {
+              let index = model.len();
+              model.worlds.push(world.atoms(arena));
+              model.successors.push(vec![]);
+              let mut rest = world.successors(arena).into_iter();
+              match rest.next() {
+                Some(successor) => {
+                  stack.push(Frame::Children {
+                    world: index,
+                    start: model.len(),
+                    rest,
+                  });
+                  Next::Refute(successor)
+                }
+                None => Next::Return(true),
+              }
+            }
+          }
+        }
+        Next::Return(found) => match stack.pop() {
+          Some(Frame::Leave(depth)) => {
+            drop(depth);
+            Next::Return(found)
+          }
+          Some(Frame::Or(other, len)) if !found => {
+            model.worlds.truncate(len);
+            model.successors.truncate(len);
+            Next::Refute(other)
+          }
+          Some(Frame::Children { world, start, mut rest }) if found => {
+            model.successors[world].push(start);
+            match rest.next() {
+              Some(successor) => {
+                stack.push(Frame::Children {
+                  world,
+                  start: model.len(),
+                  rest,
+                });
+                Next::Refute(successor)
+              }
+              None => Next::Return(true),
+            }
+          }
+          Some(_) => Next::Return(found),
+          None => return Ok(if found { Some(model) } else { None }),
+        },
+      };
+    }
+  }
+
+  /// 世界`world`を取り除いたモデルを返します．根でない世界は，その直後の世界を直前の世界に付け替えて取り除き，
+  /// 根は直後の世界が1つであればその世界に置き換えます．取り除けなければ[None]を返します．
+  fn without(&self, world: usize) -> Option<Self> {
+    if world == 0 && self.successors[0].len() != 1 {
+      return None;
+    }
+
+    let index = |w: usize| if w > world { w - 1 } else { w };
+    let mut model = Self {
+      worlds: vec![],
+      successors: vec![],
+    };
+    for (w, atoms) in self.worlds.iter().enumerate().filter(|&(w, _)| w != world) {
+      let mut successors = vec![];
+      for &successor in &self.successors[w] {
+        if successor == world {
+          successors.extend(self.successors[world].iter().map(|&s| index(s)));
+        } else {
+          successors.push(index(successor));
+        }
+      }
+      model.worlds.push(atoms.clone());
+      model.successors.push(successors);
+    }
+    Some(model)
+  }
+
+  /// 根の世界が前提`premises`をすべて強制し，結論`conclusion`を強制しないかを返します．
+  fn refutes(&self, arena: &Arena, premises: &[FormulaId], conclusion: FormulaId) -> bool {
+    let mut roots = premises.to_vec();
+    roots.push(conclusion);
+    let forcing = self.forcing(arena, &roots);
+    premises.iter().all(|premise| forcing[premise][0]) && !forcing[&conclusion][0]
+  }
+
+  /// 論理式`roots`とそのすべての部分式について，各世界がそれを強制するかを求めます．
+  /// 部分式は先に格納されるため番号が小さく，後続の世界も番号が大きいため，どちらも小さいものから順に求まります．
+  fn forcing(&self, arena: &Arena, roots: &[FormulaId]) -> HashMap<FormulaId, Vec<bool>> {
+    let mut logics = BTreeSet::new();
+    let mut stack = roots.to_vec();
+    while let Some(logic) = stack.pop() {
+      if logics.insert(logic) {
+        stack.extend(arena[logic].children());
+      }
+    }
+
+    let mut res: HashMap<FormulaId, Vec<bool>> = HashMap::new();
+    for logic in logics {
+      let mut values = vec![false; self.len()];
+      for w in (0..self.len()).rev() {
+        let later = self.successors[w].iter().all(|&s| values[s]);
+        let value = |logic: &FormulaId| res[logic][w];
+        values[w] = match arena[logic] {
+          Formula::Base(ref name) => self.worlds[w].binary_search(name).is_ok(),
+          Formula::Cont => false,
+          Formula::Not(left) => !value(&left) && later,
+          Formula::And(left, right) => value(&left) && value(&right),
+          Formula::Or(left, right) => value(&left) || value(&right),
+          Formula::To(left, right) => (!value(&left) || value(&right)) && later,
+          Formula::Iff(left, right) => value(&left) == value(&right) && later,
+        };
+      }
+      res.insert(logic, values);
+    }
+    res
+  }
+
+  /// 世界の数です．世界は0から順に番号が振られ，0が根です．
+  pub fn len(&self) -> usize {
+    self.worlds.len()
+  }
+
+  /// 世界がないかを返します．反例として得られるモデルは，必ず根の世界を持ちます．
+  pub fn is_empty(&self) -> bool {
+    self.worlds.is_empty()
+  }
+
+  /// 世界`world`で強制される原子式の名前を，名前順に返します．
+  pub fn atoms(&self, world: usize) -> &[String] {
+    &self.worlds[world]
+  }
+
+  /// 世界`world`の直後の世界を返します．
+  pub fn successors(&self, world: usize) -> &[usize] {
+    &self.successors[world]
+  }
+
+  /// 世界`world`が論理式`logic`を強制するかを返します．
+  pub fn forces(&self, world: usize, logic: &Logic) -> bool {
+    let mut arena = Arena::new();
+    let logic = arena.insert(logic);
+    self.forcing(&arena, &[logic])[&logic][world]
+  }
+
+  /// 設定された形式でクリプキモデルを出力します．
+  pub fn print(&self, format: ModelFormat) -> String {
+    match format {
+      ModelFormat::Plain => self.to_string(),
+      ModelFormat::Graphviz => self.graphviz(),
+      ModelFormat::Tikz => self.tex(),
+    }
+  }
+
+  /// Graphvizの`digraph`を出力します．各世界には，強制される原子式を添えます．
+  pub fn graphviz(&self) -> String {
+    let mut res = String::from("digraph kripke {\n  rankdir=BT;\n  node [shape=box];\n");
+    for (world, atoms) in self.worlds.iter().enumerate() {
+      let label = if atoms.is_empty() {
+        format!("w{}", world)
+      } else {
+        format!("w{} ⊩ {}", world, atoms.join(", "))
+      };
+      res.push_str(&format!("  w{} [label=\"{}\"];\n", world, label));
+    }
+    for (world, successors) in self.successors.iter().enumerate() {
+      for successor in successors {
+        res.push_str(&format!("  w{} -> w{};\n", world, successor));
+      }
+    }
+    res.push_str("}\n");
+    res
+  }
+
+  /// 木を描くときの各世界の横の位置を，葉を左から等間隔に並べ，葉でない世界を直後の世界の両端の中央に置いて決めます．
+  fn positions(&self) -> Vec<f64> {
+    let mut xs = vec![0.0; self.len()];
+    let mut leaves = 0;
+    for (x, successors) in xs.iter_mut().zip(&self.successors) {
+      if successors.is_empty() {
+        *x = leaves as f64;
+        leaves += 1;
+      }
+    }
+    for world in (0..self.len()).rev() {
+      if let (Some(&first), Some(&last)) = (self.successors[world].first(), self.successors[world].last()) {
+        xs[world] = (xs[first] + xs[last]) / 2.0;
+      }
+    }
+    xs
+  }
+
+  /// 世界の深さを返します．
+  fn depths(&self) -> Vec<usize> {
+    let mut depths = vec![0; self.worlds.len()];
+    for world in 0..self.worlds.len() {
+      for &successor in &self.successors[world] {
+        depths[successor] = depths[world] + 1;
+      }
+    }
+    depths
+  }
+}
+
+impl TeX for Kripke {
+  /// TikZの`tikzpicture`環境を出力します．各世界には，強制される原子式を`\Vdash`で添えます．
+  fn tex(&self) -> String {
+    let xs = self.positions();
+    let depths = self.depths();
+
+    let mut res = String::from("\\begin{tikzpicture}\n");
+    for (world, atoms) in self.worlds.iter().enumerate() {
+      let atoms: Vec<String> = atoms.iter().map(|a| Logic::Base(a.clone()).tex()).collect();
+      let label = if atoms.is_empty() {
+        format!("w_{{{}}}", world)
+      } else {
+        format!("w_{{{}}} \\Vdash {}", world, atoms.join(", "))
+      };
+      res.push_str(&format!(
+        "  \\node (w{}) at ({}, {}) {{${}$}};\n",
+        world,
+        xs[world] * 2.0,
+        depths[world] as f64 * 1.5,
+        label
+      ));
+    }
+    for (world, successors) in self.successors.iter().enumerate() {
+      for successor in successors {
+        res.push_str(&format!("  \\draw (w{}) -- (w{});\n", world, successor));
+      }
+    }
+    res.push_str("\\end{tikzpicture}\n");
+    res
+  }
+}
+
+impl Display for Kripke {
+  /// 世界，後続の関係，各世界で強制される原子式を1行ずつ出力します．
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let worlds: Vec<String> = (0..self.worlds.len()).map(|w| format!("w{}", w)).collect();
+    writeln!(f, "worlds: {}", worlds.join(", "))?;
+
+    let order: Vec<String> = self
+      .successors
+      .iter()
+      .enumerate()
+      .flat_map(|(w, successors)| successors.iter().map(move |v| format!("w{} ≤ w{}", w, v)))
+      .collect();
+    if !order.is_empty() {
+      writeln!(f, "order: {}", order.join(", "))?;
+    }
+
+    for (world, atoms) in self.worlds.iter().enumerate() {
+      if atoms.is_empty() {
+        writeln!(f, "w{} forces no atoms", world)?;
+      } else {
+        writeln!(f, "w{} forces {}", world, atoms.join(", "))?;
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Logic {
+  /// 論理式を強制しない世界を根とするクリプキモデルを探します．直観主義論理上証明可能であれば[None]を返します．
+  pub fn countermodel(&self) -> Option<Kripke> {
+    self.countermodel_with(Limits::default()).unwrap_or(None)
+  }
+
+  /// 探索に用いる資源を`limits`で制限して，論理式を強制しない世界を根とするクリプキモデルを探します．
+  pub fn countermodel_with(&self, limits: Limits) -> Result<Option<Kripke>, Resource> {
+    Kripke::find(&[], self, &Rc::new(Budget::new(limits)))
+  }
+}
+
+impl Sequent {
+  /// 前提をすべて強制し，結論を強制しない世界を根とするクリプキモデルを探します．
+  /// 推件が直観主義論理上証明可能であれば[None]を返します．
+  pub fn countermodel(&self) -> Option<Kripke> {
+    self.countermodel_with(Limits::default()).unwrap_or(None)
+  }
+
+  /// 探索に用いる資源を`limits`で制限して，前提をすべて強制し，結論を強制しない世界を根とするクリプキモデルを探します．
+  pub fn countermodel_with(&self, limits: Limits) -> Result<Option<Kripke>, Resource> {
+    Kripke::find(&self.premises, &self.conclusion, &Rc::new(Budget::new(limits)))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+
+  #[test]
+  fn test_countermodel() {
+    let logic = Logic::new("not not A to A").unwrap();
+    let model = logic.countermodel().unwrap();
+    assert_eq!(
+      model.to_string(),
+      "worlds: w0, w1\norder: w0 ≤ w1\nw0 forces no atoms\nw1 forces A\n"
+    );
+    assert_eq!(
+      model.graphviz(),
+      [
+        "digraph kripke {",
+        "  rankdir=BT;",
+        "  node [shape=box];",
+        "  w0 [label=\"w0\"];",
+        "  w1 [label=\"w1 ⊩ A\"];",
+        "  w0 -> w1;",
+        "}",
+        "",
+      ]
+      .join("\n")
+    );
+    assert_eq!(
+      model.tex(),
+      [
+        "\\begin{tikzpicture}",
+        "  \\node (w0) at (0, 0) {$w_{0}$};",
+        "  \\node (w1) at (0, 1.5) {$w_{1} \\Vdash A$};",
+        "  \\draw (w0) -- (w1);",
+        "\\end{tikzpicture}",
+        "",
+      ]
+      .join("\n")
+    );
+
+    for s in [
+      "A or not A",
+      "((A to B) to A) to A",
+      "not (A and B) to not A or not B",
+      "(A to B or C) to (A to B) or (A to C)",
+      "(A iff B) or (A iff not B)",
+      "((A to B) to C) to ((B to A) to C) to C",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      assert!(logic.solve(None).is_err(), "{}", s);
+      let model = logic.countermodel().unwrap();
+      assert!(!model.forces(0, &logic), "{}", s);
+    }
+
+    let sequent: Sequent = "not A to B, not B |- A".parse().unwrap();
+    let model = sequent.countermodel().unwrap();
+    assert!(sequent.premises.iter().all(|p| model.forces(0, p)));
+    assert!(!model.forces(0, &sequent.conclusion));
+
+    for s in ["not not (A or not A)", "(A to B) to not B to not A", "A and B to B"] {
+      assert!(Logic::new(s).unwrap().countermodel().is_none(), "{}", s);
+    }
+  }
+
+  #[test]
+  fn test_limits() {
+    let logic = Logic::new("((A to B) to C) to ((B to A) to C) to C").unwrap();
+    let limits = Limits {
+      max_nodes: Some(2),
+      ..Limits::default()
+    };
+    assert_eq!(logic.countermodel_with(limits), Err(Resource::Nodes(2)));
+    let limits = Limits {
+      max_depth: Some(1),
+      ..Limits::default()
+    };
+    assert_eq!(logic.countermodel_with(limits), Err(Resource::Depth(1)));
+  }
+
+  #[test]
+  fn test_deep() {
+    // 深い論理式や多くの世界を持つモデルでも，スタックを溢れさせません．
+    const DEPTH: usize = 10000;
+    let logic = Logic::new(&format!("{} to B", vec!["A"; DEPTH].join(" to "))).unwrap();
+    let model = logic.countermodel().unwrap();
+    assert_eq!(model.to_string(), "worlds: w0\nw0 forces A\n");
+    assert!(Logic::new(&vec!["A"; DEPTH + 1].join(" to ")).unwrap().countermodel().is_none());
+
+    let model = Kripke {
+      worlds: (0..DEPTH).map(|w| if w + 1 < DEPTH { vec![] } else { vec!["A".to_string()] }).collect(),
+      successors: (1..=DEPTH).map(|w| if w < DEPTH { vec![w] } else { vec![] }).collect(),
+    };
+    assert!(!model.forces(0, &Logic::new("A or not A").unwrap()));
+    assert!(model.forces(0, &Logic::new("not not A").unwrap()));
+    assert!(model.tex().ends_with("\\end{tikzpicture}\n"));
+  }
+}
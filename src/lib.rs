@@ -2,9 +2,11 @@
 //!
 //! ## 概要
 //!
-//! 命題論理ソルバーです．TeX記法等でインラインで証明したい論理式を入力すると，証明図を吐きます．出力には，簡略化した記法とTeX記法のいずれかが選べます．
+//! 命題論理ソルバーです．TeX記法等でインラインで証明したい論理式を入力すると，証明図を吐きます．出力には，簡略化した記法とTeX記法（proof.sty，bussproofs.sty），Fitch式の線形な証明（簡略化した記法とfitch.sty）のいずれかが選べます．シーケント計算やヒルベルト流の公理系による導出も出力できます．
 //!
-//! 仮定の参照先が明示されるようになりました．
+//! 仮定の参照先が明示されるようになりました．各推論に用いた規則（`[→I]`など）も表示されます．
+//!
+//! 証明の探索には直観主義命題論理の決定手続き（DyckhoffのLJT）を用いるため，必ず停止し，証明できなかった場合はその論理式が直観主義論理上証明不可能であることを意味します．探索の順序，参照番号，反例の選び方は入力のみで決まり，同じ入力には常に同じ出力を返します．
 //!
 //! ## インストール
 //! ```bash
@@ -21,32 +23,237 @@
 //!
 //! ```bash
 //! $ prop-logic "((A or B) to C) to (A to C) and (B to C)"
-//! (A ∨ B → C) → (A → C) ∧ (B → C) : 1
-//! + (A → C) ∧ (B → C)
-//!   + A → C : 2
-//!   | + C
-//!   |   + A ∨ B
+//! (A ∨ B → C) → (A → C) ∧ (B → C) [→I] : 1
+//! + (A → C) ∧ (B → C) [∧I]
+//!   + A → C [→I] : 2
+//!   | + C [→E]
+//!   |   + A ∨ B [∨I]
 //!   |   | + A from: 2
 //!   |   + A ∨ B → C from: 1
-//!   + B → C : 3
-//!     + C
-//!       + A ∨ B
+//!   + B → C [→I] : 3
+//!     + C [→E]
+//!       + A ∨ B [∨I]
 //!       | + B from: 3
 //!       + A ∨ B → C from: 1
 //! ```
 //!
 //! * 記法
+//!   * 原子式：英字で始まる英数字の列です（`A`，`P1`，`rain`など）．
+//!     * `p_1`，`p_{12}`，`p₁`のように添字を付けられます．`p_1`と`p_{1}`と`p₁`は同じ原子式です．
+//!     * `not`，`and`，`or`，`to`，`cont`は原子式の名前にできません．
+//!   * `cont | \perp | ⊥`：矛盾です．
 //!   * `not | \lnot`：否定（…でない）です．
 //!   * `and | \land`：論理積（かつ）です．
 //!   * `or | \lor`：論理和（または）です．
 //!   * `to | \to `：論理包含（ならば）です．
+//!   * `iff | \leftrightarrow | \iff | ↔`：同値（…のとき，かつそのときに限り）です．
 //!
 //! * かっこ`()`について
 //!   * 優先順位を指定します．
-//!   * 同じ二項演算（`and, or, to`）を繰り返す場合はかっこが必要です．
-//!     * 例：`(A and B) and C to A and (B and C)`
-//!   * 記法の項で上にあるものほど優先して計算されます：
-//!     * 例：`not A and B to (A \to B)`は`((not A) and B) to (A to B)`に同じです．
+//!   * 記法の項で上にあるものほど優先して計算されます（`¬ > ∧ > ∨ > → > ↔`）：
+//!     * 例：`not A and B or C to D`は`(((not A) and B) or C) to D`に同じです．
+//!   * `to`は右結合，`and, or, iff`は左結合で，同じ二項演算を繰り返す場合もかっこは不要です：
+//!     * 例：`A to B to C`は`A to (B to C)`に，`A and B and C`は`(A and B) and C`に同じです．
+//!   * 出力では，同じ規則で必要な箇所にのみかっこを付けます．出力をそのまま入力すると同じ論理式になります．
+//!
+//! * 構文エラー
+//!   * 入力を解釈できない場合は，その行と列，期待された字句（原子式，`)`，演算子など）を出力し，該当する文字を`^`で示します．
+//!   * 論理式の後ろに余分な文字列がある場合もエラーになります．
+//!
+//! ```bash
+//! $ prop-logic "A to B )"
+//! error when parsing:
+//! line 1, column 8: expected operator, `,`, `⊢` or end of input
+//! A to B )
+//!        ^
+//! ```
+//!
+//! * 推件
+//!   * `A to B, A |- B`のように，ターンスタイル（`|- | \vdash | ⊢`）の左にカンマ区切りで前提を書けます．
+//!   * 前提は閉じられない仮定として証明に用いられ，証明図では参照番号のない葉になります．
+//!   * 前提から結論が古典論理上帰結しない場合は，反例を出力します．
+//!
+//! ```bash
+//! $ prop-logic "A to B, A |- B"
+//! B [→E]
+//! + A
+//! + A → B
+//! ```
+//!
+//! * 古典論理
+//!   * 既定では直観主義論理の規則のみで証明します．
+//!   * 直観主義論理上証明不可能な論理式（`A or not A`など）は，古典論理上恒真であっても`could not infer`となり，反例となるクリプキモデルを出力します．
+//!   * `-c, --classical <rule>`を指定すると，以下のいずれかの規則を加えた古典論理で証明します．
+//!     * `lem`：排中律（`A ∨ ¬A`を前提なしに導く）
+//!     * `dne`：二重否定の除去（`¬¬A`から`A`を導く）
+//!     * `raa`：背理法（`¬A`を仮定して`⊥`を導き，`A`を導く）
+//!   * 古典論理の規則は，直観主義論理で証明できない原子式または論理和を導くときにのみ用います．
+//!   * 古典論理の規則も，他の規則と同様に証明図に`[LEM]`のように表示されます．
+//!
+//! ```bash
+//! $ prop-logic -c raa "not not A to A"
+//! ¬ ¬ A → A [→I] : 1
+//! + A [RAA] : 2
+//!   + ⊥ [¬E]
+//!     + ¬ A from: 2
+//!     + ¬ ¬ A from: 1
+//! ```
+//!
+//! * 正規化
+//!   * `-n, --normalize`を指定すると，証明図を正規化してから出力します．シーケント計算の導出は正規化しないため，`-s`とは同時に指定できません．
+//!   * 導入した論理式をすぐに除去する回り道（Prawitzの簡約）と，仮定を用いない枝を持つ論理和の除去を取り除きます．
+//!
+//! ```bash
+//! $ prop-logic -n "(A or B) and (C and D) to D"
+//! (A ∨ B) ∧ (C ∧ D) → D [→I] : 1
+//! + D [∧E]
+//!   + C ∧ D [∧E]
+//!     + (A ∨ B) ∧ (C ∧ D) from: 1
+//! ```
+//!
+//! * シーケント計算
+//!   * `-s, --sequent-calculus`を指定すると，自然演繹の代わりにシーケント計算の導出を出力します．
+//!   * 既定ではLJで，`-c, --classical`を指定した場合は（選んだ規則によらず）LKで導出します．いずれも構造規則を論理規則に吸収した体系（G3i，G3c）で，公理`Γ, A ⊢ A, Δ`は任意の論理式について認めます．
+//!   * 出力形式は`plain`，`proof`，`bussproofs`が選べます．`fitch`，`fitch-tex`はそれぞれ`plain`，`proof`と同じになります．
+//!
+//! ```bash
+//! $ prop-logic -s "A to B, B to C |- A to C"
+//! A → B, B → C ⊢ A → C [→R]
+//! + A → B, B → C, A ⊢ C [→L]
+//!   + A → B, B → C, A ⊢ A [Ax]
+//!   + B → C, A, B ⊢ C [→L]
+//!     + B → C, A, B ⊢ B [Ax]
+//!     + A, B, C ⊢ C [Ax]
+//! ```
+//!
+//! * ヒルベルト流の公理系
+//!   * `-a, --axiomatic`を指定すると，自然演繹の証明図を演繹定理によってヒルベルト流の導出に翻訳して出力します．`-s`とは同時に指定できません．
+//!   * 推論規則はモーダスポネンス（MP）のみで，各行は推件の前提，公理図式の実例，上の行からのMPのいずれかです．
+//!   * 公理図式は，論理包含の`K`：`A → B → A`と`S`：`(A → B → C) → (A → B) → A → C`に加え，各論理結合子の導入と除去（`∧I`：`A → B → A ∧ B`，`∨E`：`(A → C) → (B → C) → A ∨ B → C`，`¬I`：`(A → ⊥) → ¬A`，`⊥E`：`⊥ → A`等）です．古典論理では選んだ規則に応じて`LEM`，`DNE`，`RAA`を加えます．
+//!   * 出力形式は`plain`，`fitch`で簡略化した記法，`proof`，`bussproofs`，`fitch-tex`でTeXの`tabular`環境になります．
+//!
+//! ```bash
+//! $ prop-logic -a "A or B to B or A"
+//! 1  A → B ∨ A                                  ∨I₂
+//! 2  B → B ∨ A                                  ∨I₁
+//! 3  (A → B ∨ A) → (B → B ∨ A) → A ∨ B → B ∨ A  ∨E
+//! 4  (B → B ∨ A) → A ∨ B → B ∨ A                MP 1, 3
+//! 5  A ∨ B → B ∨ A                              MP 2, 4
+//! ```
+//!
+//! * 証明の検査
+//!   * `prop-logic --check <input>`で，手書きの証明図が`<input>`の正しい証明であるかを検査します．証明図は`--proof <file>`で指定したファイルから，省略すれば標準入力から読み込みます．
+//!   * 証明図は標準出力用の証明図と同じ形式で書きます．出力をそのまま入力しても検査できます．
+//!     * 1行に1つの推論を書き，行頭の`+ `の位置で深さを示します．各行は，それより上で1つ浅い最も近い行の前提になります．
+//!     * 推論規則は`[→I]`のように書きます．`[toI]`，`[\to I]`，`[lem]`のような英字やTeX記法の名前も使えます．
+//!     * 仮定を閉じる推論には` : 1`のように参照番号を，仮定には` from: 1`のように閉じる推論の参照番号を付けます．何も付けない葉は推件の前提です．
+//!   * 推論規則に従っていない推論，参照番号の誤り，閉じられていない仮定があれば，その行番号を出力します．空行は無視しますが，行番号には数えます．
+//!
+//! ```bash
+//! $ cat proof.txt
+//! A ∧ B → B ∧ A [→I] : 1
+//! + B ∧ A [∧I]
+//!   + B [∧E]
+//!   | + A ∧ B from: 1
+//!   + A [∧E]
+//!     + A ∧ B from: 2
+//! $ prop-logic --check --proof proof.txt "A and B to B and A"
+//! error when checking proof:
+//! line 6: A ∧ B is not discharged: no inference below has label 2
+//! ```
+//!
+//! * 反例
+//!   * 入力が古典論理上証明不可能な場合は，論理式を偽にする真偽値の割り当て（反例）を原子式の名前順に出力します．
+//!   * `-r, --report <report>`で反例の報告方法を選べます．
+//!     * `first`：最初に見つかった反例のみを出力します（既定）．
+//!     * `all`：すべての反例を真理値表と同じ順に出力します．
+//!     * `dnf`：論理式が偽になる条件を，簡約した選言標準形で出力します．
+//!
+//! ```bash
+//! $ prop-logic -r dnf "A or B to A and C"
+//! error when checking:
+//! A ∨ B → A ∧ C turns out false when: (A ∧ ¬ C) ∨ (¬ A ∧ B)
+//! ```
+//!
+//! * 証明できなかった場合
+//!   * 探索が行き詰まった箇所を`stuck here`として報告します．可逆な規則で問題を分解し尽くした後，可逆でない規則をすべて試みて失敗した目的の論理式と，その箇所で用いることができた仮定（`assumptions in scope`），試みた推論規則とその対象（`rules tried`）を出力します．
+//!   * 直観主義論理で証明できなかった場合は，その反例となる有限のクリプキモデルを添えて報告します．ソルバの限界ではなく，直観主義論理上証明不可能であることを示します．
+//!     * 世界`w0`が根で，前提をすべて強制し，結論を強制しません．`order`は各世界の直後の世界で，到達可能性はその反射推移閉包です．
+//!     * 各世界で強制される原子式は，到達可能な世界でも強制されます．
+//!   * `prop-logic --countermodel <input>`で，反例のみを出力します．`--model-format <format>`で出力形式（`plain`，`graphviz`，`tikz`）を選べます．`graphviz`では`digraph`を，`tikz`では`tikzpicture`環境を，根を下にして出力します．
+//!
+//! ```bash
+//! $ prop-logic "(A to B or C) to (A to B) or (A to C)"
+//! error when solving:
+//! could not infer: (A → B) ∨ (A → C)
+//! stuck here:
+//!   assumptions in scope: A → B ∨ C
+//!   rules tried: ∨I on (A → B) ∨ (A → C)
+//! Kripke countermodel of the input:
+//!   worlds: w0, w1, w2
+//!   order: w0 ≤ w1, w0 ≤ w2
+//!   w0 forces no atoms
+//!   w1 forces A, C
+//!   w2 forces A, B
+//! ```
+//!
+//! ```bash
+//! $ prop-logic --countermodel --model-format graphviz "not not A to A"
+//! digraph kripke {
+//!   rankdir=BT;
+//!   node [shape=box];
+//!   w0 [label="w0"];
+//!   w1 [label="w1 ⊩ A"];
+//!   w0 -> w1;
+//! }
+//! ```
+//!
+//! * 探索の制限
+//!   * `--max-depth <n>`で入れ子になった問題の深さを，`--max-nodes <n>`で探索する問題の数を，`--timeout <ms>`でミリ秒単位の経過時間を制限します．既定ではどれも制限しません．
+//!   * `-s`を指定した場合は，シーケント計算の導出の探索も同じく制限します．
+//!   * 制限に達すると，証明不可能とはせずに探索を打ち切った旨を報告します．
+//!
+//! ```bash
+//! $ prop-logic --max-nodes 5 "(A iff B) and (B iff C) to (A iff C)"
+//! error when solving:
+//! search explored more than 5 nodes
+//! ```
+//!
+//! * 出力形式
+//!   * `-f, --format <format>`で証明図の出力形式を選べます．
+//!     * `plain`：簡略化した記法です（既定）．
+//!     * `proof`：proof.styの`\infer`を用いたTeX記法です．`-t, --tex`と同じです．
+//!     * `bussproofs`：bussproofs.styの`\AxiomC`，`\UnaryInfC`等を用いたTeX記法です．`prooftree`環境ごと出力します．
+//!     * `fitch`：Fitch式（旗式）の線形な証明です．副証明を縦線で，仮定の終わりを横線で示し，各行に推論規則と前提となる行番号（副証明は`2–4`のような範囲）を添えます．
+//!     * `fitch-tex`：fitch.styの`\hypo`，`\have`，`\open`，`\close`を用いたFitch式のTeX記法です．`nd`環境ごと出力します．
+//!
+//! ```bash
+//! $ prop-logic -f fitch "A or B to B or A"
+//! 1 │ │ A ∨ B
+//!   │ ├──────
+//! 2 │ │ │ A
+//!   │ │ ├──
+//! 3 │ │ │ B ∨ A      ∨I 2
+//! 4 │ │ │ B
+//!   │ │ ├──
+//! 5 │ │ │ B ∨ A      ∨I 4
+//! 6 │ │ B ∨ A        ∨E 1, 2–3, 4–5
+//! 7 │ A ∨ B → B ∨ A  →I 1–6
+//! ```
+//!
+//! * 真理値表
+//!   * `prop-logic --table <input>`で，論理式とそのすべての部分式の真理値表を出力します．
+//!   * `--table-format <format>`で出力形式（`plain`，`markdown`，`csv`，`tex`）を選べます．`tex`では`tabular`環境を出力します．
+//!
+//! ```bash
+//! $ prop-logic --table "A to not B"
+//! A | B | ¬ B | A → ¬ B
+//! --+---+-----+--------
+//! T | T | F   | F
+//! T | F | T   | T
+//! F | T | F   | T
+//! F | F | T   | T
+//! ```
 //!
 //! * 引数一覧
 //!
@@ -60,28 +267,99 @@
 //!     prop-logic [FLAGS] [OPTIONS] [input]
 //!
 //! FLAGS:
-//!     -h, --help           Prints help information
-//!     -i, --interactive    execute in interactive mode
-//!     -t, --tex            output in TeX format (bussproof.sty)
-//!     -V, --version        Prints version information
+//!     -a, --axiomatic           output a Hilbert-style derivation from axioms by modus ponens
+//!         --check               check a natural-deduction proof of the input written in the plain output format
+//!         --countermodel        print a Kripke countermodel of an intuitionistically unprovable input instead of a proof
+//!     -h, --help                Prints help information
+//!     -n, --normalize           normalize the proof by eliminating detours
+//!     -s, --sequent-calculus    output a derivation in sequent calculus (LJ, or LK if classical)
+//!         --table               print the truth table of the input and all its subformulas instead of a proof
+//!     -t, --tex                 output in TeX format (proof.sty); same as `--format proof`
+//!     -V, --version             Prints version information
 //!
 //! OPTIONS:
-//!     -o, --out <out>    output file (if omitted, stdout)
+//!     -c, --classical <classical>          solve in classical logic with the given rule (lem, dne or raa)
+//!     -f, --format <format>                output format (plain, proof, bussproofs, fitch or fitch-tex) [default: plain]
+//!         --max-depth <max-depth>          abort the search beyond the given depth of nested subgoals
+//!         --max-nodes <max-nodes>          abort the search after exploring the given number of subgoals
+//!         --model-format <model-format>    countermodel format (plain, graphviz or tikz) [default: plain]
+//!     -o, --out <out>                      output file (if omitted, stdout)
+//!         --proof <proof>                  file containing the proof to check (if omitted, stdin)
+//!     -r, --report <report>                how to report counterexamples (first, all or dnf) [default: first]
+//!         --table-format <table-format>    truth table format (plain, markdown, csv or tex) [default: plain]
+//!         --timeout <timeout>              abort the search after the given number of milliseconds
 //!
 //! ARGS:
-//!     <input>    text input
+//!     <input>    text input (if omitted, starts in interactive mode)
+//! ```
+//!
+//! ## ライブラリとして使う
+//!
+//! `Cargo.toml`の`[dependencies]`に追加すると，論理式の構築，証明，証明図の走査をプログラムから行えます．
+//!
+//! * `Logic`：論理式の木です．`Logic::new`または`str::parse`で文字列から生成するほか，列挙子から直接構築できます．
+//! * `Sequent`：前提と結論からなる推件です．
+//! * `Arena`，`FormulaId`：論理式をハッシュコンシングして格納するアリーナと，格納された論理式の番号です．同じ論理式は同じ番号で示されるため，部分式は共有され，比較とハッシュ値の計算は定数時間で行えます．証明探索と古典論理上の検証はアリーナの上で行います．
+//!   * `insert`で`Logic`を格納し，`logic`で`Logic`に戻します．
+//!   * `Problem::in_arena`で複数の問題に同じアリーナを共有させると，まとめて解くときに共通する部分式を一度だけ格納できます．`Inference::formula`で推論の論理式の番号を返します．
+//! * `Problem`，`Inference`：推論すべき問題と，得られた証明図の各ノードです．
+//!   * `conclusion`で導かれる論理式を，`rule`で用いた推論規則を，`premises`で前提となる推論を返します．
+//!   * `discharged`でその推論で閉じられる仮定を，`is_assumption`，`is_premise`で仮定の種類を返します．
+//!   * `normalize`で正規化した証明図を，`size`で証明図に現れる論理式の数を返します．
+//! * `Proof`：入力の論理式から独立した，所有された証明図です．`Inference::to_proof`で変換します．
+//!   * 論理式を`Arc`で共有し，仮定の参照番号を整数で持つため，`Send + Sync + 'static`です．
+//!   * `Proof::new`で手書きの証明図も構築できます．標準出力用の証明図と同じ形式の文字列から`str::parse`で読み込むこともできます．`check`で，各推論が推論規則に従っているか，仮定の参照番号が正しいか，閉じられていない仮定が残っていないかを検査し，誤りのある行を`ProofError`で返します．`check_sequent`では，根の論理式が推件の結論と一致するかも検査します．
+//!   * `fitch`，`fitch_tex`でFitch式の証明を出力します．`Inference`からも同名のメソッドで出力できます．
+//! * `Derivation`：シーケント計算の導出です．`Sequent::derive`で，LJ（引数が`true`ならLK）による導出を探索します．`rule`で各推論に用いた規則（`SequentRule`）を返します．
+//! * `Hilbert`：ヒルベルト流の導出です．`Proof::hilbert`（`Inference::hilbert`）で証明図から翻訳し，`lines`で各行の論理式と根拠（`Justification`）を返します．
+//! * `SolveError`：証明に失敗した場合のエラーです．
+//!   * `Unprovable`は探索を尽くしても証明できなかった場合で，探索が行き詰まった箇所`Stuck`を持ちます．`logic`で目的の論理式を，`assumptions`でその箇所の仮定を，`attempts`で試みた推論規則を，`countermodel`で直観主義論理の反例となるクリプキモデルを返します．
+//!   * `ResourceExhausted`は資源の制限に達して探索を打ち切った場合で，原因となった制限`Resource`を持ちます．
+//! * `Limits`：証明探索に用いる資源の制限です．深さ，問題の数，経過時間の上限と，他のスレッドから探索を打ち切るための`Arc<AtomicBool>`を設定し，`Logic::solve_with`，`Sequent::solve_with`，`Sequent::derive_with`または`Problem::limit`に渡します．
+//! * `Kripke`：クリプキモデルです．`Logic::countermodel`，`Sequent::countermodel`で反例を探します．`countermodel_with`では，証明探索と同じ`Limits`で探索に用いる資源を制限します．`forces`で各世界が論理式を強制するかを，`graphviz`，`tex`で図を出力します．
+//! * `TeX`：TeX記法で出力するTraitです．
+//!
+//! ```rust
+//! use prop_logic::{Logic, Rule, TeX};
+//!
+//! let logic = Logic::new("A and B to B").unwrap();
+//! let inference = logic.solve(None).unwrap();
+//! assert_eq!(inference.rule(), Some(Rule::ToIntro));
+//! assert_eq!(inference.discharged(), vec![Logic::new("A and B").unwrap()]);
+//! assert_eq!(inference.premises()[0].conclusion().tex(), "B");
 //! ```
 
+mod arena;
+mod budget;
+mod calculus;
+mod checker;
 mod cli;
 mod exec;
+mod fitch;
+mod hilbert;
+mod kripke;
 mod logic;
 mod parser;
+mod proof;
 mod solver;
+mod table;
 
+pub use arena::*;
+pub use budget::{Limits, Resource};
+pub use calculus::*;
+pub use checker::*;
 pub use cli::*;
 pub use exec::*;
+pub use hilbert::*;
+pub use kripke::*;
+pub use logic::*;
+pub use parser::ParseLogicError;
+pub use proof::*;
+pub use solver::*;
+pub use table::*;
 
-/// TeX記法で出力可能であることを意味するTraitです．`Logic`と`Inference`に実装されています．
-trait TeX {
+/// TeX記法で出力可能であることを意味するTraitです．`Logic`，`Inference`等に実装されています．
+pub trait TeX {
+  /// TeX記法の文字列を返します．
   fn tex(&self) -> String;
 }
@@ -1,213 +1,631 @@
 //! 論理式を示す[Logic]列挙子を定義し，関連する関数を実装するするモジュールです．
 
-use std::collections::{HashMap, HashSet};
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt::Display;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
-use super::{parser, solver::*, TeX};
+use super::{arena::*, budget::Limits, parser, solver::*, TeX};
 
 /// 論理式を示す列挙子です．木構造のノードです．
-#[derive(Debug, PartialEq, Hash, Clone)]
+/// 原子式は`P`，`rain`，`p_1`のような名前を持ちます．添字は`_`で区切った形に正規化されます．
+///
+/// 複製，比較，ハッシュ値の計算，評価と出力は，深く入れ子になった論理式でもスタックを溢れさせないよう，
+/// 木を再帰せずに明示的なスタックでたどります．
+#[derive(Debug)]
 pub enum Logic {
-  Base(char),
+  Base(String),
   Cont,
   Not(Box<Self>),
   And(Box<Self>, Box<Self>),
   Or(Box<Self>, Box<Self>),
   To(Box<Self>, Box<Self>),
+  Iff(Box<Self>, Box<Self>),
 }
 
 impl FromStr for Logic {
   type Err = parser::ParseLogicError;
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    parser::parse(s)
-      .map(|(_, logic)| logic)
-      .map_err(|err| err.map_input(|str| str.to_string()))
+    parser::logic(s)
   }
 }
 
 impl Logic {
   /// 文字列リテラルから新たな論理式の木を生成します．`s.parse() as Logic`と同じです．
-  pub fn new<'a>(s: &'a str) -> Result<Self, parser::ParseLogicError> {
+  pub fn new(s: &str) -> Result<Self, parser::ParseLogicError> {
     Self::from_str(s)
   }
 
   /// 論理のメソッドで，その論理式を解くメソッドを呼び出します．
-  pub fn solve(&self) -> Result<Inference, SolveError> {
-    Problem::new(self).solve()
+  /// `classical`が[Some]であれば，その規則を加えた古典論理で解きます．
+  pub fn solve(&self, classical: Option<Classical>) -> Result<Inference, SolveError> {
+    self.solve_with(classical, Limits::default())
+  }
+
+  /// 探索に用いる資源を`limits`で制限して，論理式を解きます．
+  pub fn solve_with(&self, classical: Option<Classical>, limits: Limits) -> Result<Inference, SolveError> {
+    Problem::new(self, &[], classical).limit(limits).solve()
+  }
+
+  /// 論理式の木の根の演算子が直接とる部分式を列挙します．
+  pub fn children(&self) -> Vec<&Self> {
+    match self {
+      Self::Base(_) | Self::Cont => vec![],
+      Self::Not(logic) => vec![logic],
+      Self::And(left, right)
+      | Self::Or(left, right)
+      | Self::To(left, right)
+      | Self::Iff(left, right) => vec![left, right],
+    }
   }
 
   /// 古典論理上証明可能かを確かめます．
-  /// 論理式にあるすべての文字に真([None])または偽([Some(Logic::Cont)])を代入することで検証しています．
+  /// 論理式にあるすべての原子式に，名前順に真または偽を代入することで検証しています．
+  /// 反例が複数あれば，真理値表で最初に現れるものを報告します．
   pub fn check_all(&self) -> Result<(), CheckError> {
-    let mut map = HashMap::new();
-    let c = self
-      .base_set()
-      .into_iter()
-      .next()
-      .ok_or(CheckError::NoBase)?;
-    for b in [true, false] {
-      map.insert(c, b);
-      match self.eval_part(&map) {
-        Some(Self::Cont) => return Err(CheckError::TurnsOutFalse(self.clone(), map)),
-        Some(logic) => logic.check_all().map_err(|s| match s {
-          CheckError::NoBase => CheckError::NoBase,
-          CheckError::TurnsOutFalse(_, mut map) => {
-            map.insert(c, b);
-            CheckError::TurnsOutFalse(self.clone(), map)
-          }
-        })?,
-        None => (),
-      };
+    self.check(Report::First)
+  }
+
+  /// 論理式の真偽値を評価します．論理式にあるすべての原子式の真偽値が`map`に与えられている必要があり，
+  /// 与えられていない原子式があれば偽を返します．
+  pub fn eval(&self, map: &BTreeMap<String, bool>) -> bool {
+    let mut arena = Arena::new();
+    let id = arena.insert(self);
+    arena.eval(id, map)
+  }
+
+  /// 古典論理上証明可能かを確かめ，証明不可能であれば`report`に従って反例を報告します．
+  pub fn check(&self, report: Report) -> Result<(), CheckError> {
+    let mut arena = Arena::new();
+    let id = arena.insert(self);
+    match refute(&arena, id, report)? {
+      Some(counterexample) => Err(CheckError::TurnsOutFalse(self.clone(), counterexample)),
+      None => Ok(()),
     }
-    Ok(())
   }
 
-  /// 論理式にあるすべての文字を列挙します．
-  fn base_set(&self) -> HashSet<char> {
+  /// 根の演算子の結合の強さです．大きいほど強く結合し，原子式，矛盾，否定が最も強く結合します．
+  /// パーサと出力で共有されます．
+  pub fn precedence(&self) -> u8 {
     match self {
-      Self::Base(c) => [c.to_owned()].iter().cloned().collect(),
-      Self::Cont => HashSet::new(),
-      Self::Not(logic) => logic.base_set(),
-      Self::And(left, right) => left.base_set().union(&right.base_set()).cloned().collect(),
-      Self::Or(left, right) => left.base_set().union(&right.base_set()).cloned().collect(),
-      Self::To(left, right) => left.base_set().union(&right.base_set()).cloned().collect(),
+      Self::Base(_) | Self::Cont | Self::Not(_) => 5,
+      Self::And(_, _) => 4,
+      Self::Or(_, _) => 3,
+      Self::To(_, _) => 2,
+      Self::Iff(_, _) => 1,
     }
   }
 
-  /// 論理式と任意の文字の真偽値をとり，その真偽値で評価できる限り真偽値を評価します．
-  fn eval_part(&self, map: &HashMap<char, bool>) -> Option<Self> {
-    match self {
-      Self::Base(c) => match map.get(c) {
-        Some(&b) => {
-          if b {
-            None
-          } else {
-            Some(Self::Cont)
-          }
+  /// 根の演算子が右結合であるかを示します．論理包含のみが右結合で，他の二項演算子は左結合です．
+  pub fn is_right_assoc(&self) -> bool {
+    matches!(self, Self::To(_, _))
+  }
+
+  /// 論理式を文字列にします．`tex`が`true`であればTeX記法で，`false`であればUnicodeの記号で出力します．
+  /// かっこは，[Logic::precedence]と[Logic::is_right_assoc]に従って必要な箇所にのみ付けます．
+  fn show(&self, tex: bool) -> String {
+    /// 出力の途中で，これから書き出すものです．
+    enum Token<'a> {
+      /// かっこが必要であれば`true`とともに持つ論理式です．
+      Logic(&'a Logic, bool),
+
+      /// そのまま書き出す文字列です．
+      Text(&'static str),
+    }
+
+    let symbol = |tex_symbol, unicode_symbol| if tex { tex_symbol } else { unicode_symbol };
+    let mut out = String::new();
+    let mut stack = vec![Token::Logic(self, false)];
+    while let Some(token) = stack.pop() {
+      let (logic, paren) = match token {
+        Token::Text(text) => {
+          out.push_str(text);
+          continue;
+        }
+        Token::Logic(logic, paren) => (logic, paren),
+      };
+      if paren {
+        out.push('(');
+        stack.push(Token::Text(")"));
+      }
+
+      let op = match logic {
+        Self::Base(name) => {
+          out.push_str(&show_base(name, tex));
+          continue;
+        }
+        Self::Cont => {
+          out.push_str(symbol("\\perp", "⊥"));
+          continue;
+        }
+        Self::Not(child) => {
+          out.push_str(symbol("\\lnot ", "¬ "));
+          stack.push(Token::Logic(child, child.precedence() < logic.precedence()));
+          continue;
+        }
+        Self::And(_, _) => symbol(" \\land ", " ∧ "),
+        Self::Or(_, _) => symbol(" \\lor ", " ∨ "),
+        Self::To(_, _) => symbol(" \\to ", " → "),
+        Self::Iff(_, _) => symbol(" \\leftrightarrow ", " ↔ "),
+      };
+
+      let (left, right) = match logic.children()[..] {
+        [left, right] => (left, right),
+        _ => unreachable!(),
+      };
+      let precedence = logic.precedence();
+      let (left_needs, right_needs) = if logic.is_right_assoc() {
+        (
+          left.precedence() <= precedence,
+          right.precedence() < precedence,
+        )
+      } else {
+        (
+          left.precedence() < precedence,
+          right.precedence() <= precedence,
+        )
+      };
+      stack.push(Token::Logic(right, right_needs));
+      stack.push(Token::Text(op));
+      stack.push(Token::Logic(left, left_needs));
+    }
+    out
+  }
+
+  /// 論理式の木を帰りがけ順にたどり，各ノードとその部分式に`f`を適用した値から，根の値を計算します．
+  fn fold<T>(&self, mut f: impl FnMut(&Self, Vec<T>) -> T) -> T {
+    let mut stack = vec![(self, false)];
+    let mut values = vec![];
+    while let Some((logic, visited)) = stack.pop() {
+      let children = logic.children();
+      if visited {
+        let values_of_children = values.split_off(values.len() - children.len());
+        values.push(f(logic, values_of_children));
+      } else {
+        stack.push((logic, true));
+        stack.extend(children.into_iter().rev().map(|child| (child, false)));
+      }
+    }
+    values.pop().unwrap()
+  }
+}
+
+/// 原子式の名前を出力します．添字`p_1`は，TeX記法では`p_{1}`に，
+/// Unicodeでは添字が数字のみであれば`p₁`になります．
+fn show_base(name: &str, tex: bool) -> String {
+  match name.split_once('_') {
+    None => name.to_string(),
+    Some((stem, sub)) if tex => format!("{}_{{{}}}", stem, sub),
+    Some((stem, sub)) if sub.chars().all(|c| c.is_ascii_digit()) => {
+      let sub: String = sub
+        .chars()
+        .map(|c| char::from_u32('₀' as u32 + c.to_digit(10).unwrap()).unwrap())
+        .collect();
+      format!("{}{}", stem, sub)
+    }
+    Some(_) => name.to_string(),
+  }
+}
+
+impl Clone for Logic {
+  fn clone(&self) -> Self {
+    self.fold(|logic, children| {
+      let mut children = children.into_iter().map(Box::new);
+      let mut child = || children.next().unwrap();
+      match logic {
+        Self::Base(name) => Self::Base(name.clone()),
+        Self::Cont => Self::Cont,
+        Self::Not(_) => Self::Not(child()),
+        Self::And(_, _) => Self::And(child(), child()),
+        Self::Or(_, _) => Self::Or(child(), child()),
+        Self::To(_, _) => Self::To(child(), child()),
+        Self::Iff(_, _) => Self::Iff(child(), child()),
+      }
+    })
+  }
+}
+
+impl PartialEq for Logic {
+  fn eq(&self, other: &Self) -> bool {
+    let mut stack = vec![];
+    let (mut left, mut right) = (self, other);
+    loop {
+      match (left, right) {
+        (Self::Base(a), Self::Base(b)) if a == b => {}
+        (Self::Cont, Self::Cont) => {}
+        (Self::Not(a), Self::Not(b)) => {
+          left = a;
+          right = b;
+          continue;
+        }
+        (Self::And(a, b), Self::And(c, d))
+        | (Self::Or(a, b), Self::Or(c, d))
+        | (Self::To(a, b), Self::To(c, d))
+        | (Self::Iff(a, b), Self::Iff(c, d)) => {
+          stack.push((b, d));
+          left = a;
+          right = c;
+          continue;
         }
-        None => Some(Self::Base(*c)),
-      },
-      Self::Cont => Some(Self::Cont),
-      Self::Not(logic) => match logic.eval_part(map) {
-        Some(Self::Cont) => None,
-        Some(logic) => Some(Self::Not(Box::new(logic))),
-        None => Some(Self::Cont),
-      },
-      Self::And(left, right) => match (left.eval_part(map), right.eval_part(map)) {
-        (Some(Self::Cont), _) => Some(Self::Cont),
-        (_, Some(Self::Cont)) => Some(Self::Cont),
-        (None, right) => right,
-        (left, None) => left,
-        (Some(left), Some(right)) => Some(Self::And(Box::new(left), Box::new(right))),
-      },
-      Self::Or(left, right) => match (left.eval_part(map), right.eval_part(map)) {
-        (None, _) => None,
-        (_, None) => None,
-        (Some(Self::Cont), right) => right,
-        (left, Some(Self::Cont)) => left,
-        (Some(left), Some(right)) => Some(Self::Or(Box::new(left), Box::new(right))),
-      },
-      Self::To(left, right) => match (left.eval_part(map), right.eval_part(map)) {
-        (Some(Self::Cont), _) => None,
-        (_, None) => None,
-        (None, right) => right,
-        (Some(left), Some(Self::Cont)) => Some(Self::Not(Box::new(left))),
-        (Some(left), Some(right)) => Some(Self::To(Box::new(left), Box::new(right))),
-      },
-    }
-  }
-
-  /// 論理式の結合順位を計算するための補助関数です．
-  fn is_low(&self) -> bool {
-    matches!(self, Self::Base(_) | Self::Cont | Self::Not(_))
+        _ => return false,
+      }
+      match stack.pop() {
+        Some((a, b)) => {
+          left = a;
+          right = b;
+        }
+        None => return true,
+      }
+    }
   }
 }
 
 impl Eq for Logic {}
 
+impl Hash for Logic {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    let mut stack = vec![self];
+    while let Some(logic) = stack.pop() {
+      std::mem::discriminant(logic).hash(state);
+      if let Self::Base(name) = logic {
+        name.hash(state);
+      }
+      stack.extend(logic.children().into_iter().rev());
+    }
+  }
+}
+
+/// 前提の列と結論からなる推件`Γ ⊢ φ`を示す構造です．前提がなければ結論を定理として証明します．
+#[derive(Debug, PartialEq, Clone)]
+pub struct Sequent {
+  /// 前提です．証明図では閉じられない仮定になります．
+  pub premises: Vec<Logic>,
+
+  /// 結論です．
+  pub conclusion: Logic,
+}
+
+impl FromStr for Sequent {
+  type Err = parser::ParseLogicError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parser::sequent(s)
+  }
+}
+
+impl Sequent {
+  /// 推件を解くメソッドを呼び出します．前提は閉じられない仮定として用いられます．
+  /// `classical`が[Some]であれば，その規則を加えた古典論理で解きます．
+  pub fn solve(&self, classical: Option<Classical>) -> Result<Inference, SolveError> {
+    self.solve_with(classical, Limits::default())
+  }
+
+  /// 探索に用いる資源を`limits`で制限して，推件を解きます．
+  pub fn solve_with(&self, classical: Option<Classical>, limits: Limits) -> Result<Inference, SolveError> {
+    Problem::new(&self.conclusion, &self.premises, classical)
+      .limit(limits)
+      .solve()
+  }
+
+  /// 前提から結論が古典論理上意味論的に帰結するかを確かめます．
+  /// 前提を左から順に仮定した論理包含`A → (B → C)`が恒真であるかを検証しています．
+  pub fn check_all(&self) -> Result<(), CheckError> {
+    self.check(Report::First)
+  }
+
+  /// 前提から結論が古典論理上意味論的に帰結するかを確かめ，帰結しなければ`report`に従って反例を報告します．
+  pub fn check(&self, report: Report) -> Result<(), CheckError> {
+    if self.premises.is_empty() {
+      return self.conclusion.check(report);
+    }
+
+    let mut arena = Arena::new();
+    let conclusion = arena.insert(&self.conclusion);
+    let id = self.premises.iter().rev().fold(conclusion, |logic, premise| {
+      let premise = arena.insert(premise);
+      arena.intern(Formula::To(premise, logic))
+    });
+    match refute(&arena, id, report)? {
+      Some(counterexample) => Err(CheckError::NotEntailed(self.clone(), counterexample)),
+      None => Ok(()),
+    }
+  }
+}
+
+/// アリーナに格納された論理式`id`が古典論理上恒真であるかを確かめ，そうでなければ`report`に従って反例を返します．
+/// 論理式に原子式がなければ[CheckError::NoBase]を，すべての割り当てを列挙する報告方法で原子式が多すぎれば[CheckError::TooManyBases]を返します．
+fn refute(arena: &Arena, id: FormulaId, report: Report) -> Result<Option<Counterexample>, CheckError> {
+  let bases: Vec<String> = arena.bases(id).into_iter().collect();
+  if bases.is_empty() {
+    return Err(CheckError::NoBase);
+  }
+
+  if report == Report::First {
+    return Ok(first_counterexample(arena, id, &bases).map(Counterexample::First));
+  }
+  if bases.len() >= usize::BITS as usize {
+    return Err(CheckError::TooManyBases(bases.len()));
+  }
+
+  let counterexamples = counterexamples(arena, id, &bases);
+  if counterexamples.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(match report {
+    Report::Dnf => Counterexample::Dnf(dnf(&bases, &counterexamples)),
+    _ => Counterexample::All(counterexamples),
+  }))
+}
+
+/// 論理式`id`を偽にする，真理値表で最初に現れる真偽値の割り当てを探します．
+/// 原子式`bases`に名前順に真，偽の順で値を代入していき，真偽が決まった時点で残りの枝を探索しません．
+/// 偽と決まった場合も，残りの原子式に真を代入してすべての原子式の割り当てを返します．
+fn first_counterexample(arena: &Arena, id: FormulaId, bases: &[String]) -> Option<BTreeMap<String, bool>> {
+  let mut stack = vec![BTreeMap::new()];
+  while let Some(mut map) = stack.pop() {
+    match arena.eval_part(id, &map) {
+      Some(true) => continue,
+      Some(false) => {
+        for base in &bases[map.len()..] {
+          map.insert(base.clone(), true);
+        }
+        return Some(map);
+      }
+      None => (),
+    }
+    let base = &bases[map.len()];
+    for value in [false, true] {
+      let mut map = map.clone();
+      map.insert(base.clone(), value);
+      stack.push(map);
+    }
+  }
+  None
+}
+
+/// 論理式`id`を偽にする真偽値の割り当てをすべて列挙します．
+/// 真理値表と同じく，すべての原子式が真である割り当てから順に並びます．
+/// 原子式の数は`usize::BITS`未満でなければなりません．
+fn counterexamples(arena: &Arena, id: FormulaId, bases: &[String]) -> Vec<BTreeMap<String, bool>> {
+  let n = bases.len();
+  (0..1usize << n)
+    .map(|i| {
+      bases
+        .iter()
+        .enumerate()
+        .map(|(j, base)| (base.clone(), i >> (n - 1 - j) & 1 == 0))
+        .collect()
+    })
+    .filter(|map| !arena.eval(id, map))
+    .collect()
+}
+
+impl TeX for Sequent {
+  fn tex(&self) -> String {
+    let premises: Vec<_> = self.premises.iter().map(|premise| premise.tex()).collect();
+    format!("{} \\vdash {}", premises.join(", "), self.conclusion.tex())
+      .trim_start()
+      .to_string()
+  }
+}
+
+impl Display for Sequent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let premises: Vec<_> = self.premises.iter().map(|premise| premise.to_string()).collect();
+    write!(
+      f,
+      "{}",
+      format!("{} ⊢ {}", premises.join(", "), self.conclusion).trim_start()
+    )
+  }
+}
+
 impl TeX for Logic {
   fn tex(&self) -> String {
+    self.show(true)
+  }
+}
+
+impl Display for Logic {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.show(false))
+  }
+}
+
+/// 反例の報告方法を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Report {
+  /// 最初に見つかった反例のみを報告します．
+  First,
+
+  /// 論理式を偽にする真偽値の割り当てをすべて報告します．
+  All,
+
+  /// 論理式が偽になる条件を，簡約した選言標準形で報告します．
+  Dnf,
+}
+
+impl FromStr for Report {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "first" => Ok(Self::First),
+      "all" => Ok(Self::All),
+      "dnf" => Ok(Self::Dnf),
+      _ => Err(format!(
+        "unknown report: {} (expected first, all or dnf)",
+        s
+      )),
+    }
+  }
+}
+
+/// 論理式を偽にする反例を示す列挙子です．真偽値の割り当ては原子式の名前順に並びます．
+#[derive(Debug, Clone, PartialEq)]
+pub enum Counterexample {
+  /// 最初に見つかった反例です．論理式が偽と決まった時点で割り当てていない原子式には真を割り当てます．
+  First(BTreeMap<String, bool>),
+
+  /// 論理式を偽にするすべての割り当てです．
+  All(Vec<BTreeMap<String, bool>>),
+
+  /// 論理式が偽になる条件を示す選言標準形です．各項は原子式の一部への割り当てで，それらの選言です．
+  Dnf(Vec<BTreeMap<String, bool>>),
+}
+
+/// 真偽値の割り当てを`A: true, B: false`の形で出力します．
+fn show_assignment(map: &BTreeMap<String, bool>) -> String {
+  let list: Vec<String> = map
+    .iter()
+    .map(|(base, b)| format!("{}: {}", Logic::Base(base.clone()), b))
+    .collect();
+  list.join(", ")
+}
+
+impl Display for Counterexample {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
-      Self::Base(c) => format!("{}", c),
-      Self::Cont => format!("\\perp"),
-      Self::Not(logic) => {
-        if logic.is_low() {
-          format!("\\lnot {}", logic.tex())
-        } else {
-          format!("\\lnot ({})", logic.tex())
+      Self::First(map) => write!(f, "when: {}", show_assignment(map)),
+      Self::All(list) => {
+        write!(f, "when any of:")?;
+        for map in list {
+          write!(f, "\n  {}", show_assignment(map))?;
         }
+        Ok(())
       }
-      Self::And(left, right) => {
-        let left = if left.is_low() {
-          format!("{}", left.tex())
-        } else {
-          format!("({})", left.tex())
-        };
-        let right = if right.is_low() {
-          format!("{}", right.tex())
-        } else {
-          format!("({})", right.tex())
-        };
-        format!("{} \\land {}", left, right)
-      }
-      Self::Or(left, right) => {
-        let left = if left.is_low() {
-          format!("{}", left.tex())
-        } else {
-          format!("({})", left.tex())
-        };
-        let right = if right.is_low() {
-          format!("{}", right.tex())
-        } else {
-          format!("({})", right.tex())
-        };
-        format!("{} \\lor {}", left, right)
-      }
-      Self::To(left, right) => {
-        let left = if let Self::To(_, _) = **left {
-          format!("({})", left.tex())
-        } else {
-          format!("{}", left.tex())
-        };
-        let right = if let Self::To(_, _) = **right {
-          format!("({})", right.tex())
-        } else {
-          format!("{}", right.tex())
-        };
-        format!("{} \\to {}", left, right)
+      Self::Dnf(terms) => {
+        let terms: Vec<String> = terms
+          .iter()
+          .map(|term| {
+            let literals: Vec<String> = term
+              .iter()
+              .map(|(base, &b)| {
+                let base = Logic::Base(base.clone());
+                if b {
+                  base.to_string()
+                } else {
+                  Logic::Not(Box::new(base)).to_string()
+                }
+              })
+              .collect();
+            match literals.len() {
+              0 => "always".to_string(),
+              1 => literals.join(""),
+              _ if terms.len() == 1 => literals.join(" ∧ "),
+              _ => format!("({})", literals.join(" ∧ ")),
+            }
+          })
+          .collect();
+        write!(f, "when: {}", terms.join(" ∨ "))
       }
     }
   }
 }
 
-impl Display for Logic {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let string = self
-      .tex()
-      .replace("\\perp", "⊥")
-      .replace("\\lnot", "¬")
-      .replace("\\land", "∧")
-      .replace("\\lor", "∨")
-      .replace("\\to", "→");
-    write!(f, "{}", string)
+/// 真偽値の割り当ての集合を，Quine-McCluskey法で簡約した選言標準形にします．
+/// 各項は`bases`の一部への割り当てで，`minterms`のすべてを覆います．
+fn dnf(bases: &[String], minterms: &[BTreeMap<String, bool>]) -> Vec<BTreeMap<String, bool>> {
+  let minterms: Vec<Vec<Option<bool>>> = minterms
+    .iter()
+    .map(|map| bases.iter().map(|base| map.get(base).cloned()).collect())
+    .collect();
+
+  // 1箇所だけ真偽値が異なる項を併合することを繰り返し，それ以上併合できない主項を集めます．
+  let mut primes: Vec<Vec<Option<bool>>> = Vec::new();
+  let mut terms = minterms.clone();
+  while !terms.is_empty() {
+    let mut used = vec![false; terms.len()];
+    let mut next: Vec<Vec<Option<bool>>> = Vec::new();
+    for i in 0..terms.len() {
+      for j in i + 1..terms.len() {
+        let diff: Vec<usize> = (0..bases.len())
+          .filter(|&k| terms[i][k] != terms[j][k])
+          .collect();
+        if let [k] = diff[..] {
+          if terms[i][k].is_some() && terms[j][k].is_some() {
+            let mut term = terms[i].clone();
+            term[k] = None;
+            used[i] = true;
+            used[j] = true;
+            if !next.contains(&term) {
+              next.push(term);
+            }
+          }
+        }
+      }
+    }
+    for (term, used) in terms.into_iter().zip(used) {
+      if !used && !primes.contains(&term) {
+        primes.push(term);
+      }
+    }
+    terms = next;
+  }
+
+  // 覆っていない割り当てを最も多く覆う主項を貪欲に選びます．
+  let covers = |prime: &[Option<bool>], minterm: &[Option<bool>]| {
+    prime
+      .iter()
+      .zip(minterm)
+      .all(|(p, m)| p.is_none() || p == m)
+  };
+  let mut uncovered = minterms.clone();
+  let mut chosen = Vec::new();
+  while !uncovered.is_empty() {
+    let best = primes
+      .iter()
+      .max_by_key(|prime| {
+        (
+          uncovered.iter().filter(|m| covers(prime, m)).count(),
+          std::cmp::Reverse(prime.iter().filter(|p| p.is_some()).count()),
+        )
+      })
+      .unwrap()
+      .clone();
+    uncovered.retain(|m| !covers(&best, m));
+    chosen.push(best);
   }
+
+  // 選んだ項を，覆う割り当てのうち最初のものの順に並べます．
+  chosen.sort_by_key(|term| minterms.iter().position(|m| covers(term, m)));
+
+  chosen
+    .into_iter()
+    .map(|term| {
+      bases
+        .iter()
+        .zip(term)
+        .filter_map(|(base, b)| b.map(|b| (base.clone(), b)))
+        .collect()
+    })
+    .collect()
 }
 
 /// 入力された論理式が古典論理上証明不可能である場合のエラーです．
 #[derive(Debug)]
 pub enum CheckError {
-  TurnsOutFalse(Logic, HashMap<char, bool>),
+  TurnsOutFalse(Logic, Counterexample),
+  NotEntailed(Sequent, Counterexample),
   NoBase,
+  TooManyBases(usize),
 }
 
 impl Display for CheckError {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
-      Self::TurnsOutFalse(logic, map) => write!(f, "{} turns out false when: {:?}", logic, map),
+      Self::TurnsOutFalse(logic, counterexample) => {
+        write!(f, "{} turns out false {}", logic, counterexample)
+      }
+      Self::NotEntailed(sequent, counterexample) => {
+        write!(f, "{} does not hold {}", sequent, counterexample)
+      }
       Self::NoBase => write!(f, "no base"),
+      Self::TooManyBases(n) => write!(
+        f,
+        "too many bases to enumerate: {} (at most {})",
+        n,
+        usize::BITS - 1
+      ),
     }
   }
 }
@@ -216,13 +634,107 @@ impl Error for CheckError {}
 
 #[cfg(test)]
 mod test {
-  ///! テストを行うサブモジュールです．あまり充実していません…
+  //! テストを行うサブモジュールです．あまり充実していません…
   use super::*;
+  use std::collections::BTreeSet;
 
   #[test]
   fn test_base_set() {
     let logic = Logic::new("(A \\lor B) \\land C \\to (A \\land C) \\lor B \\land C").unwrap();
-    let expect: HashSet<_> = ['A', 'B', 'C'].iter().cloned().collect();
-    assert_eq!(logic.base_set(), expect);
+    let expect: BTreeSet<_> = ["A", "B", "C"].iter().map(|s| s.to_string()).collect();
+    let mut arena = Arena::new();
+    let id = arena.insert(&logic);
+    assert_eq!(arena.bases(id), expect);
+  }
+
+  #[test]
+  fn test_sequent_check_all() {
+    let sequent: Sequent = "A to B, A |- B".parse().unwrap();
+    assert!(sequent.check_all().is_ok());
+    let sequent: Sequent = "A to B, B |- A".parse().unwrap();
+    assert!(sequent.check_all().is_err());
+  }
+
+  #[test]
+  fn test_iff_check_all() {
+    assert!(Logic::new("(A iff B) iff (B iff A)").unwrap().check_all().is_ok());
+    assert!(Logic::new("A iff not A to B").unwrap().check_all().is_err());
+  }
+
+  #[test]
+  fn test_check_report() {
+    let logic = Logic::new("A and B to C").unwrap();
+    match logic.check(Report::All) {
+      Err(CheckError::TurnsOutFalse(_, counterexample)) => assert_eq!(
+        counterexample.to_string(),
+        "when any of:\n  A: true, B: true, C: false"
+      ),
+      _ => panic!(),
+    }
+
+    let logic = Logic::new("A and B or C to D").unwrap();
+    match logic.check(Report::First) {
+      Err(CheckError::TurnsOutFalse(_, counterexample)) => assert_eq!(
+        counterexample.to_string(),
+        "when: A: true, B: true, C: true, D: false"
+      ),
+      _ => panic!(),
+    }
+
+    let logic = Logic::new("A or B to A and C").unwrap();
+    match logic.check(Report::Dnf) {
+      Err(CheckError::TurnsOutFalse(_, counterexample)) => {
+        assert_eq!(counterexample.to_string(), "when: (A ∧ ¬ C) ∨ (¬ A ∧ B)")
+      }
+      _ => panic!(),
+    }
+  }
+
+  #[test]
+  fn test_too_many_bases() {
+    let bases: Vec<String> = (0..64).map(|i| format!("P{}", i)).collect();
+    let logic = Logic::new(&bases.join(" and ")).unwrap();
+    for report in [Report::All, Report::Dnf] {
+      match logic.check(report) {
+        Err(CheckError::TooManyBases(64)) => (),
+        _ => panic!(),
+      }
+    }
+    match logic.check(Report::First) {
+      Err(CheckError::TurnsOutFalse(_, Counterexample::First(map))) => {
+        assert_eq!(map.len(), 64);
+        assert_eq!(map.values().filter(|&&b| !b).count(), 1);
+      }
+      _ => panic!(),
+    }
+  }
+
+  #[test]
+  fn test_show_reparse() {
+    for s in [
+      "A and B or C to D",
+      "(A or B) and C",
+      "A and (B and C)",
+      "(A to B) to C",
+      "A to B to C",
+      "not (A or B) iff C iff (D iff E)",
+      "(A iff B) to not not C and D",
+      "not (A to cont) or cont",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      assert_eq!(Logic::new(&logic.tex()).unwrap(), logic);
+      assert_eq!(Logic::new(&logic.to_string()).unwrap(), logic);
+    }
+    assert_eq!(
+      Logic::new("((A and B) or C) to D").unwrap().to_string(),
+      "A ∧ B ∨ C → D"
+    );
+  }
+
+  #[test]
+  fn test_show_base() {
+    let logic = Logic::new("p_{12} and rain to P1").unwrap();
+    assert_eq!(logic.tex(), "p_{12} \\land rain \\to P1");
+    assert_eq!(logic.to_string(), "p₁₂ ∧ rain → P1");
   }
 }
@@ -2,117 +2,589 @@
 //! 詳しくは[公式ドキュメント](https://docs.rs/nom/7.1.0/nom/)を参照してください．
 //! 用いたBNFは以下です：
 //! ```bnf
-//! <base>  := A-Z
-//! <cont>  := '\perp '
+//! <sub>   := '_' ( <alnum>+ | '{' <alnum>+ '}' ) | ( '₀'-'₉' )+
+//! <base>  := <alpha> <alnum>* <sub>?    ただしキーワードを除く
+//! <cont>  := '\perp'
 //! <paren> := '(' ws0 <parse> ws0 ')'
 //! <term>  := <base> | <cont> | <paren> | <not>
 //! <not>   := '\lnot ' ws0 ( <term> )
-//! <and>   := <term> ws0 '\land ' ws0 ( <and> | <term> )
-//! <or>    := <term> ws0 '\land ' ws0 ( <or> | <term> )
-//! <to>    := ( <and> | <or> | <term> ) ws0 '\land ' ws0 <parse>
-//! <parse> := <to> | <and> | <or> | <term>
+//! <op>    := '\land ' | '\lor ' | '\to ' | '\leftrightarrow '
+//! <parse> := <term> ( ws0 <op> ws0 <term> )*
+//! <seq>   := ( <parse> ( ws0 ',' ws0 <parse> )* )? ws0 '\vdash ' ws0 <parse> | <parse>
+//! <rule>  := '[' ws0 <name> ws0 ']'
+//! <step>  := <parse> ( ws0 <rule> ( ws0 ':' ws0 <digit>+ )? | ws0 'from:' ws0 <digit>+ )?
+//! <line>  := ( ( '| ' | '  ' )* '+ ' )? <step>
 //! ```
+//!
+//! `<parse>`の二項演算子は優先順位法でパースします．結合の強さは`¬ > ∧ > ∨ > → > ↔`で，
+//! `→`は右結合，`∧`，`∨`，`↔`は左結合です．
+//! 英字の演算子（`not`，`and`，`to`等）は，英数字または`_`が続く場合は演算子として読みません．
+//!
+//! 入力は末尾の空白を除いてすべて読み切る必要があります．失敗した場合は[ParseLogicError]を返し，
+//! 失敗した行と列，その位置で期待された字句（原子式，`)`，演算子等）を報告します．
+//!
+//! 証明図は標準出力用の証明図と同じ形式で，1行に1つの推論を`<line>`として書きます．
+//! 行頭の`+ `の位置が深さを示し，各行はそれより上で1つ浅い最も近い行の前提になります．
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::Display;
 
 use nom::{
-  branch::*, bytes::complete::*, character::complete::*, combinator::*, error::Error, sequence::*,
-  Err, IResult,
+  branch::*,
+  bytes::complete::*,
+  character::complete::*,
+  combinator::*,
+  error::{ErrorKind, ParseError},
+  multi::*,
+  sequence::*,
+  Err, IResult, Offset,
 };
 
-use super::logic::*;
+use super::{logic::*, proof::*, solver::Rule};
 
-pub type ParseLogicError = Err<Error<String>>;
+/// パース中に失敗した位置と，その位置で期待された字句の一覧です．nomのエラー型として用います．
+/// 複数の候補が失敗したときは，最も先まで読み進めたものを残します．
+#[derive(Debug, PartialEq)]
+pub struct Expected<'a> {
+  /// 失敗した位置以降の入力です．
+  input: &'a str,
 
-/// 原子式をパースします．BNFは
-/// `<base> := A-Z`です．
-fn base(s: &str) -> IResult<&str, Logic> {
-  map(one_of("ABCDEFGHIJKLMNOPQRSTUVWXYZ"), |c| Logic::Base(c))(s)
+  /// 期待された字句の名前です．
+  tokens: Vec<&'static str>,
 }
 
-/// 矛盾をパースします．BNFは
-/// `<cont> := '\perp '`です．
-fn cont(s: &str) -> IResult<&str, Logic> {
-  value(Logic::Cont, alt((tag("\\perp "), tag("cont"), tag("⊥"))))(s)
-}
-
-/// かっこを含む式をパースします．BNFは
-/// `<paren> := '(' ws0 <parse> ws0 ')'`です．
-fn paren(s: &str) -> IResult<&str, Logic> {
-  delimited(
-    char('('),
-    delimited(multispace0, parse, multispace0),
-    char(')'),
-  )(s)
+impl<'a> ParseError<&'a str> for Expected<'a> {
+  fn from_error_kind(input: &'a str, _: ErrorKind) -> Self {
+    Self {
+      input,
+      tokens: vec![],
+    }
+  }
+
+  fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+    other
+  }
+
+  fn or(mut self, other: Self) -> Self {
+    // 残りの入力が短いほうが先まで読み進めています．
+    match self.input.len().cmp(&other.input.len()) {
+      Ordering::Less => self,
+      Ordering::Greater => other,
+      Ordering::Equal => {
+        for token in other.tokens {
+          if !self.tokens.contains(&token) {
+            self.tokens.push(token);
+          }
+        }
+        self
+      }
+    }
+  }
 }
 
-/// 否定を含む式をパースします．BNFは
-/// `<not> := '\lnot ' ws0 ( <term> )`です．
-fn not(s: &str) -> IResult<&str, Logic> {
-  map(
-    tuple((
-      alt((tag("\\lnot "), tag("not"), tag("¬"))),
-      multispace0,
-      term,
-    )),
-    |t| Logic::Not(Box::new(t.2)),
-  )(s)
+/// このモジュールのパーサの返り値です．
+type ParseResult<'a, T> = IResult<&'a str, T, Expected<'a>>;
+
+/// パーサ`f`が入力の先頭で失敗したとき，期待された字句を`token`とします．
+/// 途中まで読み進めてから失敗したときは，そのエラーをそのまま返します．
+fn expect<'a, T>(
+  token: &'static str,
+  mut f: impl FnMut(&'a str) -> ParseResult<'a, T>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, T> {
+  move |s| match f(s) {
+    Err(Err::Error(err)) if err.input.len() == s.len() => Err(Err::Error(Expected {
+      input: s,
+      tokens: vec![token],
+    })),
+    res => res,
+  }
 }
 
-/// 原子式，矛盾，かっこを含む式，否定を含む式のいずれかです．BNFは
-/// `<term> := <base> | <cont> | <paren> | <not>`です．
-fn term(s: &str) -> IResult<&str, Logic> {
-  alt((base, cont, paren, not))(s)
+/// 論理式，推件や証明図のパースに失敗したときのエラーです．失敗した行と列，期待された字句を持ち，
+/// 表示すると該当する行とその位置を示すキャレットを出力します．
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseLogicError {
+  /// 失敗した位置を含む行です．
+  source: String,
+
+  /// 失敗した位置の行番号です．1から始まります．
+  pub line: usize,
+
+  /// 失敗した位置の列番号です．1から始まり，文字単位で数えます．
+  pub column: usize,
+
+  /// 期待された字句の名前です．
+  pub expected: Vec<&'static str>,
 }
 
-/// 論理積を含む式をパースします．BNFは
-/// `<and> := <term> ws0 '\land ' ws0 ( <and> | <term> )`です．
-fn and(s: &str) -> IResult<&str, Logic> {
-  map(
-    tuple((
-      term,
-      multispace0,
-      alt((tag("\\land "), tag("and"), tag("∧"))),
-      multispace0,
-      alt((and, term)),
-    )),
-    |t| Logic::And(Box::new(t.0), Box::new(t.4)),
+impl ParseLogicError {
+  /// 入力全体`input`と，nomのエラーが持つ残りの入力からエラーを生成します．
+  /// 残りの入力は`input`の一部であれば，途中の行で切られていても構いません．
+  fn new(input: &str, err: Expected) -> Self {
+    let offset = input.offset(err.input);
+    let before = &input[..offset];
+    let start = before.rfind('\n').map_or(0, |i| i + 1);
+    let end = input[offset..].find('\n').map_or(input.len(), |i| offset + i);
+    Self {
+      source: input[start..end].trim_end_matches('\r').to_string(),
+      line: before.matches('\n').count() + 1,
+      column: before[start..].chars().count() + 1,
+      expected: err.tokens,
+    }
+  }
+}
+
+impl Display for ParseLogicError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "line {}, column {}: expected ", self.line, self.column)?;
+    match self.expected.split_last() {
+      Some((last, [])) => writeln!(f, "{}", last)?,
+      Some((last, init)) => writeln!(f, "{} or {}", init.join(", "), last)?,
+      None => writeln!(f, "nothing")?,
+    }
+    writeln!(f, "{}", self.source)?;
+    write!(f, "{}^", " ".repeat(self.column - 1))
+  }
+}
+
+impl Error for ParseLogicError {}
+
+/// 原子式の名前にできない，演算子等を示すキーワードです．
+const KEYWORDS: [&str; 6] = ["not", "and", "or", "to", "iff", "cont"];
+
+/// 英字からなるキーワード`word`をパースします．原子式の名前の先頭を読まないよう，
+/// 英数字または`_`が続く場合は失敗します．
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+  terminated(tag(word), not(peek(satisfy(|c| c.is_alphanumeric() || c == '_'))))
+}
+
+/// 原子式の添字をパースし，数字または英字の列として返します．BNFは
+/// `<sub> := '_' ( <alnum>+ | '{' <alnum>+ '}' ) | ( '₀'-'₉' )+`です．
+fn sub(s: &str) -> ParseResult<'_, String> {
+  alt((
+    map(
+      preceded(
+        char('_'),
+        alt((delimited(char('{'), alphanumeric1, char('}')), alphanumeric1)),
+      ),
+      |sub: &str| sub.to_string(),
+    ),
+    map(many1(one_of("₀₁₂₃₄₅₆₇₈₉")), |sub| {
+      sub
+        .into_iter()
+        .map(|c| char::from_u32(c as u32 - '₀' as u32 + '0' as u32).unwrap())
+        .collect()
+    }),
+  ))(s)
+}
+
+/// 原子式をパースします．添字は`p_1`の形に正規化されます．BNFは
+/// `<base> := <alpha> <alnum>* <sub>?`です．ただし[KEYWORDS]は原子式になりません．
+fn base(s: &str) -> ParseResult<'_, Logic> {
+  expect(
+    "atom",
+    map(
+      pair(
+        verify(recognize(pair(alpha1, alphanumeric0)), |stem: &str| {
+          !KEYWORDS.contains(&stem)
+        }),
+        opt(sub),
+      ),
+      |(stem, sub)| match sub {
+        Some(sub) => Logic::Base(format!("{}_{}", stem, sub)),
+        None => Logic::Base(stem.to_string()),
+      },
+    ),
   )(s)
 }
 
-/// 論理積を含む式をパースします．BNFは
-/// `<or> := <term> ws0 '\land ' ws0 ( <or> | <term> )`です．
-fn or(s: &str) -> IResult<&str, Logic> {
-  map(
-    tuple((
-      term,
-      multispace0,
-      alt((tag("\\lor "), tag("or"), tag("∨"))),
-      multispace0,
-      alt((or, term)),
-    )),
-    |t| Logic::Or(Box::new(t.0), Box::new(t.4)),
+/// 矛盾をパースします．BNFは
+/// `<cont> := '\perp'`です．
+fn cont(s: &str) -> ParseResult<'_, Logic> {
+  expect(
+    "`⊥`",
+    value(Logic::Cont, alt((tag("\\perp"), keyword("cont"), tag("⊥")))),
   )(s)
 }
 
-/// 論理包含を含む式をパースします．BNFは
-/// `<to> := ( <and> | <or> | <term> ) ws0 '\land ' ws0 <parse>`です．
-fn to(s: &str) -> IResult<&str, Logic> {
-  map(
-    tuple((
-      alt((and, or, term)),
-      multispace0,
-      alt((tag("\\to "), tag("to"), tag("→"))),
-      multispace0,
-      parse,
+/// 項の先頭の字句を示す列挙子です．
+#[derive(Clone)]
+enum Prefix {
+  /// 原子式または矛盾です．これだけで項になります．
+  Term(Logic),
+
+  /// 開きかっこです．かっこを含む式が続きます．
+  Paren,
+
+  /// 否定です．項が続きます．
+  Not,
+}
+
+/// 項の先頭の字句をパースします．原子式と矛盾のほか，かっこを含む式と否定を含む式の先頭を読みます．BNFは
+/// `<paren> := '(' ws0 <parse> ws0 ')'`，`<term> := <base> | <cont> | <paren> | <not>`，
+/// `<not> := '\lnot ' ws0 ( <term> )`です．
+fn prefix(s: &str) -> ParseResult<'_, Prefix> {
+  alt((
+    map(base, Prefix::Term),
+    map(cont, Prefix::Term),
+    value(Prefix::Paren, pair(expect("`(`", char('(')), multispace0)),
+    value(
+      Prefix::Not,
+      pair(
+        expect("`¬`", alt((tag("\\lnot "), keyword("not"), tag("¬")))),
+        multispace0,
+      ),
+    ),
+  ))(s)
+}
+
+/// 二項演算子を生成する関数です．[Logic::And]等の列挙子がこの型をとります．
+type Binary = fn(Box<Logic>, Box<Logic>) -> Logic;
+
+/// 二項演算子をパースし，その演算子の式を生成する関数を返します．BNFは
+/// `<op> := '\land ' | '\lor ' | '\to ' | '\leftrightarrow '`です．
+fn op(s: &str) -> ParseResult<'_, Binary> {
+  expect(
+    "operator",
+    alt((
+      value(
+        Logic::And as Binary,
+        alt((tag("\\land "), keyword("and"), tag("∧"))),
+      ),
+      value(
+        Logic::Or as Binary,
+        alt((tag("\\lor "), keyword("or"), tag("∨"))),
+      ),
+      value(
+        Logic::To as Binary,
+        alt((tag("\\to "), keyword("to"), tag("→"))),
+      ),
+      value(
+        Logic::Iff as Binary,
+        alt((
+          tag("\\leftrightarrow "),
+          tag("\\iff "),
+          keyword("iff"),
+          tag("↔"),
+        )),
+      ),
     )),
-    |t| Logic::To(Box::new(t.0), Box::new(t.4)),
   )(s)
 }
 
+/// パース中に，続きを読み終えるまで待っている構造を示す列挙子です．
+enum Pending {
+  /// 否定です．続く項を読み終えたら適用します．
+  Not,
+
+  /// 開きかっこです．対応する閉じかっこまでを1つの項にします．
+  Paren,
+
+  /// 左の被演算子を読み終えた二項演算子と，その結合の強さです．
+  Binary(Logic, Binary, u8),
+}
+
 /// 任意の論理式をパースします．BNFは
-/// `<parse> := <to> | <and> | <or> | <term>`です．
-/// 他のモジュールから呼び出されます．
-pub fn parse(s: &str) -> IResult<&str, Logic> {
-  alt((to, and, or, term))(s)
+/// `<parse> := <term> ( ws0 <op> ws0 <term> )*`で，二項演算子の結合は優先順位に従います．
+///
+/// 二項演算子は優先順位法でパースします．結合の強さと結合の向きは[Logic::precedence]と[Logic::is_right_assoc]に従います．
+/// 深く入れ子になった入力でもスタックを溢れさせないよう，読み終えていない否定，かっこ，二項演算子を明示的なスタックに積みます．
+fn parse(mut s: &str) -> ParseResult<'_, Logic> {
+  let mut stack = vec![];
+  loop {
+    let mut left = loop {
+      let (rest, prefix) = prefix(s)?;
+      s = rest;
+      match prefix {
+        Prefix::Term(logic) => break logic,
+        Prefix::Paren => stack.push(Pending::Paren),
+        Prefix::Not => stack.push(Pending::Not),
+      }
+    };
+
+    loop {
+      while let Some(Pending::Not) = stack.last() {
+        stack.pop();
+        left = Logic::Not(Box::new(left));
+      }
+
+      // 演算子が続けば，それより強く結合する左側の演算子を適用してから積み，右の被演算子を読みます．
+      match preceded(multispace0, op)(s) {
+        Ok((rest, build)) => {
+          let probe = build(Box::new(Logic::Cont), Box::new(Logic::Cont));
+          let precedence = probe.precedence();
+          loop {
+            match stack.last() {
+              Some(Pending::Binary(_, _, p))
+                if *p > precedence || (*p == precedence && !probe.is_right_assoc()) => {}
+              _ => break,
+            }
+            if let Some(Pending::Binary(l, b, _)) = stack.pop() {
+              left = b(Box::new(l), Box::new(left));
+            }
+          }
+          stack.push(Pending::Binary(left, build, precedence));
+          s = multispace0(rest)?.0;
+          break;
+        }
+        Err(Err::Error(_)) => {}
+        Err(err) => return Err(err),
+      }
+
+      // 演算子が続かなければ，開きかっこまでの演算子をすべて適用し，かっこを閉じます．
+      while let Some(Pending::Binary(..)) = stack.last() {
+        if let Some(Pending::Binary(l, b, _)) = stack.pop() {
+          left = b(Box::new(l), Box::new(left));
+        }
+      }
+      match stack.pop() {
+        None => return Ok((s, left)),
+        Some(Pending::Paren) => {
+          s = preceded(multispace0, expect("`)`", char(')')))(s)?.0;
+        }
+        Some(_) => unreachable!("every pending operator has been applied"),
+      }
+    }
+  }
+}
+
+/// ターンスタイルをパースします．
+fn turnstile(s: &str) -> ParseResult<'_, &str> {
+  expect("`⊢`", alt((tag("\\vdash "), tag("|-"), tag("⊢"))))(s)
+}
+
+/// 前提をとる推件をパースします．ターンスタイルがなければ前提のない推件になります．BNFは
+/// `<seq> := ( <parse> ( ws0 ',' ws0 <parse> )* )? ws0 '\vdash ' ws0 <parse> | <parse>`です．
+///
+/// 前提と結論のどちらか分からないまま式を読み進めるため，`separated_list0`等を使わずに
+/// 式の直後で期待される字句を明示しています．
+fn seq(s: &str) -> ParseResult<'_, Sequent> {
+  let mut formulas = vec![];
+  let mut s = s;
+  if let Ok((rest, _)) = preceded(multispace0, turnstile)(s) {
+    let (rest, conclusion) = preceded(multispace0, parse)(rest)?;
+    return Ok((
+      rest,
+      Sequent {
+        premises: formulas,
+        conclusion,
+      },
+    ));
+  }
+  loop {
+    let (rest, logic) = preceded(multispace0, parse)(s)?;
+    formulas.push(logic);
+    let (rest, _) = multispace0(rest)?;
+    if let Ok((rest, _)) = char::<_, Expected>(',')(rest) {
+      s = rest;
+      continue;
+    }
+    if let Ok((rest, _)) = turnstile(rest) {
+      let (rest, conclusion) = preceded(multispace0, parse)(rest)?;
+      return Ok((
+        rest,
+        Sequent {
+          premises: formulas,
+          conclusion,
+        },
+      ));
+    }
+    if formulas.len() == 1 && rest.is_empty() {
+      return Ok((
+        rest,
+        Sequent {
+          premises: vec![],
+          conclusion: formulas.pop().unwrap(),
+        },
+      ));
+    }
+    let mut tokens = vec!["operator", "`,`", "`⊢`"];
+    if formulas.len() == 1 {
+      tokens.push("end of input");
+    }
+    return Err(Err::Error(Expected { input: rest, tokens }));
+  }
+}
+
+/// 入力の末尾をパースします．末尾の空白は無視します．
+fn end(s: &str) -> ParseResult<'_, ()> {
+  if s.trim_end().is_empty() {
+    Ok(("", ()))
+  } else {
+    Err(Err::Error(Expected {
+      input: s.trim_start(),
+      tokens: vec!["operator", "end of input"],
+    }))
+  }
+}
+
+/// 入力全体`s`に対するnomのエラーを[ParseLogicError]に変換します．
+fn error(s: &str, err: Err<Expected>) -> ParseLogicError {
+  match err {
+    Err::Error(err) | Err::Failure(err) => ParseLogicError::new(s, err),
+    Err::Incomplete(_) => unreachable!("complete parsers never return Incomplete"),
+  }
+}
+
+/// `parser`で入力全体をパースし，失敗すれば[ParseLogicError]に変換します．
+fn all<'a, T>(
+  s: &'a str,
+  parser: impl FnMut(&'a str) -> ParseResult<'a, T>,
+) -> Result<T, ParseLogicError> {
+  terminated(parser, end)(s)
+    .map(|(_, res)| res)
+    .map_err(|err| error(s, err))
+}
+
+/// 文字列全体を論理式としてパースします．他のモジュールから呼び出されます．
+pub fn logic(s: &str) -> Result<Logic, ParseLogicError> {
+  all(s, preceded(multispace0, parse))
+}
+
+/// 文字列全体を推件としてパースします．他のモジュールから呼び出されます．
+pub fn sequent(s: &str) -> Result<Sequent, ParseLogicError> {
+  all(s, seq)
+}
+
+/// 証明図の行の末尾をパースします．末尾でなければ，その位置で期待された字句`tokens`を報告します．
+fn eol<'a>(s: &'a str, tokens: &[&'static str]) -> ParseResult<'a, ()> {
+  if s.is_empty() {
+    Ok((s, ()))
+  } else {
+    let mut tokens = tokens.to_vec();
+    tokens.push("end of line");
+    Err(Err::Error(Expected { input: s, tokens }))
+  }
+}
+
+/// 仮定の参照番号をパースします．
+fn label(s: &str) -> ParseResult<'_, usize> {
+  expect("label", map_opt(digit1, |label: &str| label.parse().ok()))(s)
+}
+
+/// 推論規則の名前をかっこごとパースします．名前は[Rule]の`from_str`に従います．BNFは
+/// `<rule> := '[' ws0 <name> ws0 ']'`です．
+fn rule(s: &str) -> ParseResult<'_, Rule> {
+  let (rest, name) = preceded(
+    pair(expect("`[`", char('[')), multispace0),
+    take_till(|c| c == ']'),
+  )(s)?;
+  let rule = name.trim_end().parse().map_err(|_| {
+    Err::Error(Expected {
+      input: name,
+      tokens: vec!["rule"],
+    })
+  })?;
+  let (rest, _) = expect("`]`", char(']'))(rest)?;
+  Ok((rest, rule))
+}
+
+/// 証明図の1行から，先頭の記号を除いた推論をパースします．前提はまだ空です．BNFは
+/// `<step> := <parse> ( ws0 <rule> ( ws0 ':' ws0 <digit>+ )? | ws0 'from:' ws0 <digit>+ )?`です．
+fn step(s: &str) -> ParseResult<'_, (Logic, Step)> {
+  let (s, logic) = parse(s)?;
+  let (s, _) = multispace0(s)?;
+
+  if let Ok((rest, _)) = tag::<_, _, Expected>("from:")(s) {
+    let (rest, label) = preceded(multispace0, label)(rest)?;
+    let (rest, _) = eol(rest, &[])?;
+    return Ok((rest, (logic, Step::Assumption(label))));
+  }
+
+  if !s.starts_with('[') {
+    let (rest, _) = eol(s, &["operator", "`[`", "`from:`"])?;
+    return Ok((rest, (logic, Step::Premise)));
+  }
+
+  let (rest, rule) = rule(s)?;
+  let (rest, _) = multispace0(rest)?;
+  let (rest, label) = match char::<_, Expected>(':')(rest) {
+    Ok((rest, _)) => {
+      let (rest, label) = preceded(multispace0, label)(rest)?;
+      let (rest, _) = eol(rest, &[])?;
+      (rest, Some(label))
+    }
+    Err(_) => {
+      let (rest, _) = eol(rest, &["`:`"])?;
+      (rest, None)
+    }
+  };
+  Ok((
+    rest,
+    (
+      logic,
+      Step::Inference {
+        rule,
+        label,
+        premises: vec![],
+      },
+    ),
+  ))
+}
+
+/// 証明図の1行の先頭の記号をパースし，その行の深さを返します．根は深さ0です．BNFは
+/// `( ( '| ' | '  ' )* '+ ' )?`です．
+fn indent(s: &str) -> ParseResult<'_, usize> {
+  let (rest, depth) = many0_count(alt((tag("| "), tag("  "))))(s)?;
+  if depth == 0 && !rest.starts_with('+') {
+    return Ok((rest, 0));
+  }
+  let (rest, _) = pair(expect("`+`", char('+')), space0)(rest)?;
+  Ok((rest, depth + 1))
+}
+
+/// 読みかけの推論の列の最後を取り除き，その前の推論の前提に加えます．前の推論がなければそのまま返します．
+fn close(stack: &mut Vec<(Logic, Step)>) -> Option<Proof> {
+  let (logic, step) = stack.pop()?;
+  let proof = Proof::new(logic, step);
+  match stack.last_mut() {
+    Some((_, Step::Inference { premises, .. })) => {
+      premises.push(proof);
+      None
+    }
+    _ => Some(proof),
+  }
+}
+
+/// 文字列全体を，標準出力用の証明図と同じ形式の証明図としてパースします．他のモジュールから呼び出されます．
+/// 空行と行末の空白は無視します．
+pub fn proof(s: &str) -> Result<Proof, ParseLogicError> {
+  let expected = |input, tokens| error(s, Err::Error(Expected { input, tokens }));
+
+  // 根から直前の行までの，前提がそろっていない推論の列です．添字が深さと一致します．
+  let mut stack: Vec<(Logic, Step)> = vec![];
+  for line in s.lines().map(str::trim_end).filter(|line| !line.is_empty()) {
+    let (rest, depth) = indent(line).map_err(|err| error(s, err))?;
+
+    // 根は1行目のみで，それ以降は直前の推論の前提か，その祖先の推論の前提にしかなれません．
+    let max = match stack.last() {
+      None => 0,
+      Some((_, Step::Inference { .. })) => stack.len(),
+      Some(_) => stack.len() - 1,
+    };
+    if stack.is_empty() && depth > 0 {
+      return Err(expected(line, vec!["atom", "`⊥`", "`(`", "`¬`"]));
+    }
+    if !stack.is_empty() && depth == 0 {
+      return Err(expected(line, vec!["`+`"]));
+    }
+    if depth > max {
+      return Err(expected(line, vec!["shallower indentation"]));
+    }
+
+    let (_, step) = step(rest).map_err(|err| error(s, err))?;
+    while stack.len() > depth {
+      close(&mut stack);
+    }
+    stack.push(step);
+  }
+
+  let mut root = None;
+  while !stack.is_empty() {
+    root = close(&mut stack);
+  }
+  root.ok_or_else(|| expected(&s[s.len()..], vec!["atom", "`⊥`", "`(`", "`¬`"]))
 }
 
 #[cfg(test)]
@@ -124,40 +596,81 @@ mod test {
 
   #[test]
   fn test_base() {
-    assert_eq!(base("A").unwrap(), ("", Base('A')));
+    assert_eq!(base("A").unwrap(), ("", Base("A".to_string())));
+    assert_eq!(base("rain").unwrap(), ("", Base("rain".to_string())));
+    assert_eq!(base("P1").unwrap(), ("", Base("P1".to_string())));
+    assert_eq!(base("p_1").unwrap(), ("", Base("p_1".to_string())));
+    assert_eq!(base("p_{12}").unwrap(), ("", Base("p_12".to_string())));
+    assert_eq!(base("p₁₂").unwrap(), ("", Base("p_12".to_string())));
+    assert!(base("not").is_err());
+  }
+
+  #[test]
+  fn test_keyword() {
+    for s in ["A tomato", "A order", "A andy", "A iffy"] {
+      let err = logic(s).unwrap_err();
+      assert_eq!((err.line, err.column), (1, 3), "{}", s);
+      assert_eq!(err.expected, vec!["operator", "end of input"], "{}", s);
+    }
+    assert!(logic("nota").is_ok());
+    assert!(logic("not_1").is_err());
+    assert!(logic("A to cont_1").is_err());
+
+    assert_eq!(
+      logic("not(A)to(B)").unwrap(),
+      To(Box::new(Not(Box::new(Base("A".to_string())))), Box::new(Base("B".to_string())))
+    );
   }
 
   #[test]
   fn test_paren() {
-    assert_eq!(paren("(A)").unwrap(), ("", Base('A')));
+    assert_eq!(parse("(A)").unwrap(), ("", Base("A".to_string())));
   }
 
   #[test]
   fn test_not() {
-    assert_eq!(not("\\lnot A").unwrap(), ("", Not(Box::new(Base('A')))));
+    assert_eq!(parse("\\lnot A").unwrap(), ("", Not(Box::new(Base("A".to_string())))));
   }
 
   #[test]
   fn test_and() {
     assert_eq!(
-      and("A \\land B").unwrap(),
-      ("", And(Box::new(Base('A')), Box::new(Base('B'))))
+      parse("A \\land B").unwrap(),
+      ("", And(Box::new(Base("A".to_string())), Box::new(Base("B".to_string()))))
     );
   }
 
   #[test]
   fn test_or() {
     assert_eq!(
-      or("A \\lor B").unwrap(),
-      ("", Or(Box::new(Base('A')), Box::new(Base('B'))))
+      parse("A \\lor B").unwrap(),
+      ("", Or(Box::new(Base("A".to_string())), Box::new(Base("B".to_string()))))
     );
   }
 
   #[test]
   fn test_to() {
     assert_eq!(
-      to("A \\to B").unwrap(),
-      ("", To(Box::new(Base('A')), Box::new(Base('B'))))
+      parse("A \\to B").unwrap(),
+      ("", To(Box::new(Base("A".to_string())), Box::new(Base("B".to_string()))))
+    );
+  }
+
+  #[test]
+  fn test_iff() {
+    assert_eq!(
+      parse("A \\leftrightarrow B").unwrap(),
+      ("", Iff(Box::new(Base("A".to_string())), Box::new(Base("B".to_string()))))
+    );
+    assert_eq!(
+      parse("A to B iff C").unwrap(),
+      (
+        "",
+        Iff(
+          Box::new(To(Box::new(Base("A".to_string())), Box::new(Base("B".to_string())))),
+          Box::new(Base("C".to_string()))
+        )
+      )
     );
   }
 
@@ -169,12 +682,12 @@ mod test {
         "",
         To(
           Box::new(To(
-            Box::new(Or(Box::new(Base('A')), Box::new(Base('B')))),
-            Box::new(Base('C'))
+            Box::new(Or(Box::new(Base("A".to_string())), Box::new(Base("B".to_string())))),
+            Box::new(Base("C".to_string()))
           )),
           Box::new(And(
-            Box::new(To(Box::new(Base('A')), Box::new(Base('C')))),
-            Box::new(To(Box::new(Base('B')), Box::new(Base('C'))))
+            Box::new(To(Box::new(Base("A".to_string())), Box::new(Base("C".to_string())))),
+            Box::new(To(Box::new(Base("B".to_string())), Box::new(Base("C".to_string()))))
           ))
         )
       )
@@ -184,10 +697,96 @@ mod test {
       (
         "",
         To(
-          Box::new(Base('A')),
-          Box::new(Not(Box::new(Not(Box::new(Base('A'))))))
+          Box::new(Base("A".to_string())),
+          Box::new(Not(Box::new(Not(Box::new(Base("A".to_string()))))))
         )
       )
     );
   }
+
+  #[test]
+  fn test_precedence() {
+    let b = |name: &str| Box::new(Base(name.to_string()));
+    assert_eq!(
+      parse("A and B or C to D").unwrap(),
+      (
+        "",
+        To(Box::new(Or(Box::new(And(b("A"), b("B"))), b("C"))), b("D"))
+      )
+    );
+    assert_eq!(
+      parse("not A and B").unwrap(),
+      ("", And(Box::new(Not(b("A"))), b("B")))
+    );
+    assert_eq!(
+      parse("A and B and C").unwrap(),
+      ("", And(Box::new(And(b("A"), b("B"))), b("C")))
+    );
+    assert_eq!(
+      parse("A or B or C").unwrap(),
+      ("", Or(Box::new(Or(b("A"), b("B"))), b("C")))
+    );
+    assert_eq!(
+      parse("A to B to C").unwrap(),
+      ("", To(b("A"), Box::new(To(b("B"), b("C")))))
+    );
+    assert_eq!(
+      parse("A iff B iff C").unwrap(),
+      ("", Iff(Box::new(Iff(b("A"), b("B"))), b("C")))
+    );
+  }
+
+  #[test]
+  fn test_sequent() {
+    assert_eq!(
+      sequent("A \\to B, A \\vdash B").unwrap(),
+      Sequent {
+        premises: vec![
+          To(Box::new(Base("A".to_string())), Box::new(Base("B".to_string()))),
+          Base("A".to_string())
+        ],
+        conclusion: Base("B".to_string())
+      }
+    );
+    assert_eq!(
+      sequent("|- A to A").unwrap(),
+      Sequent {
+        premises: vec![],
+        conclusion: To(Box::new(Base("A".to_string())), Box::new(Base("A".to_string())))
+      }
+    );
+    assert_eq!(
+      sequent("A to A\n").unwrap(),
+      Sequent {
+        premises: vec![],
+        conclusion: To(Box::new(Base("A".to_string())), Box::new(Base("A".to_string())))
+      }
+    );
+  }
+
+  #[test]
+  fn test_error() {
+    let err = logic("A to B )").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 8));
+    assert_eq!(err.expected, vec!["operator", "end of input"]);
+    assert_eq!(
+      err.to_string(),
+      "line 1, column 8: expected operator or end of input\nA to B )\n       ^"
+    );
+
+    let err = logic("(A and B").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 9));
+    assert_eq!(err.expected, vec!["`)`"]);
+
+    let err = logic("¬A ∧").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 5));
+    assert_eq!(err.expected, vec!["atom", "`⊥`", "`(`", "`¬`"]);
+
+    let err = sequent("A, B").unwrap_err();
+    assert_eq!(err.expected, vec!["operator", "`,`", "`⊢`"]);
+
+    let err = sequent("A\nB").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 1));
+    assert_eq!(err.expected, vec!["operator", "`,`", "`⊢`", "end of input"]);
+  }
 }
@@ -0,0 +1,377 @@
+//! 推論の結果を，入力された論理式から独立した所有された証明図として表すモジュールです．
+//! 論理式は[Arc]で共有され，仮定の参照番号は整数で持つため，スレッド間で送受信したり，
+//! 入力より長く保持したりできます．証明図の出力もこのモジュールで行います．
+//! 標準出力用の証明図と同じ形式の文字列から，手書きの証明図を読み込むこともできます．
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::{logic::*, parser, solver::Rule, TeX};
+
+/// 所有された証明図です．木構造のノードで，仮定以外では証明図の横線と一対一対応します．
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Proof {
+  /// この推論で導かれる論理式です．
+  conclusion: Arc<Logic>,
+
+  /// 推論のタイプです．
+  step: Step,
+}
+
+/// 証明図のノードのタイプを示す列挙子です．
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step {
+  /// 仮定です．値はこの仮定を閉じる推論の参照番号です．
+  Assumption(usize),
+
+  /// 推件の前提として与えられた，閉じられない仮定です．
+  Premise,
+
+  /// 推論規則による推論です．`label`は仮定を閉じる場合の参照番号です．
+  Inference {
+    rule: Rule,
+    label: Option<usize>,
+    premises: Vec<Proof>,
+  },
+}
+
+impl FromStr for Proof {
+  type Err = parser::ParseLogicError;
+  /// 標準出力用の証明図と同じ形式の文字列を証明図としてパースします．推論が正しいかは検査しません．
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parser::proof(s)
+  }
+}
+
+impl Proof {
+  /// 導かれる論理式と推論のタイプから証明図を生成します．
+  pub fn new(conclusion: impl Into<Arc<Logic>>, step: Step) -> Self {
+    Self {
+      conclusion: conclusion.into(),
+      step,
+    }
+  }
+
+  /// この推論で導かれる論理式です．
+  pub fn conclusion(&self) -> &Logic {
+    &self.conclusion
+  }
+
+  /// この推論で導かれる論理式を，共有されたまま返します．
+  pub fn conclusion_arc(&self) -> &Arc<Logic> {
+    &self.conclusion
+  }
+
+  /// 推論のタイプです．
+  pub fn step(&self) -> &Step {
+    &self.step
+  }
+
+  /// この推論に用いた推論規則です．仮定であれば[None]です．
+  pub fn rule(&self) -> Option<Rule> {
+    match self.step {
+      Step::Inference { rule, .. } => Some(rule),
+      _ => None,
+    }
+  }
+
+  /// この推論が閉じる仮定の参照番号です．
+  pub fn label(&self) -> Option<usize> {
+    match self.step {
+      Step::Inference { label, .. } => label,
+      _ => None,
+    }
+  }
+
+  /// この推論の前提となる推論を，証明図で左にあるものから順に返します．仮定であれば空です．
+  pub fn premises(&self) -> &[Proof] {
+    match self.step {
+      Step::Inference { ref premises, .. } => premises,
+      _ => &[],
+    }
+  }
+
+  /// 証明図の大きさです．証明図に現れる論理式の数を数えます．
+  pub fn size(&self) -> usize {
+    let mut stack = vec![self];
+    let mut size = 0;
+    while let Some(proof) = stack.pop() {
+      size += 1;
+      stack.extend(proof.premises());
+    }
+    size
+  }
+
+  /// 標準出力用の証明図出力を行う関数です．
+  /// 深い証明図でもスタックを溢れさせないよう，出力する推論と，その行の先頭，前提の字下げを明示的なスタックに積みます．
+  fn print(&self, tree: &mut String, indent: &str) {
+    let mut stack = vec![(self, String::new(), indent.to_string())];
+    while let Some((proof, head, indent)) = stack.pop() {
+      let marker = match proof.step {
+        Step::Assumption(label) => format!(" from: {}", label),
+        Step::Inference {
+          label: Some(label), ..
+        } => format!(" : {}", label),
+        _ => String::new(),
+      };
+
+      let rule = match proof.rule() {
+        Some(rule) => format!(" [{}]", rule),
+        None => String::new(),
+      };
+
+      tree.push_str(&format!("{}{}{}{}\n", head, proof.conclusion, rule, marker));
+      let premises = proof.premises();
+      for (i, premise) in premises.iter().enumerate().rev() {
+        let bar = if i + 1 < premises.len() { "| " } else { "  " };
+        stack.push((premise, format!("{}+ ", indent), format!("{}{}", indent, bar)));
+      }
+    }
+  }
+
+  /// TeX記法用の証明図で横線に添えるラベルを計算します．推論規則と，仮定を閉じる場合はその参照番号からなります．
+  fn label_tex(&self) -> Option<String> {
+    let rule = self.rule().map(|rule| rule.tex());
+    let marker = self.label().map(|label| label.to_string());
+
+    match (rule, marker) {
+      (Some(rule), Some(marker)) => Some(format!("{}\\ {}", rule, marker)),
+      (Some(label), None) | (None, Some(label)) => Some(label),
+      (None, None) => None,
+    }
+  }
+
+  /// TeX記法（proof.sty）用の証明図出力を行う関数です．
+  /// 前提を書き出した後に閉じかっこを書き出すため，推論と書き出す文字列を明示的なスタックに積みます．
+  fn print_tex(&self, tree: &mut String, indent: &str) {
+    let mut stack = vec![Print::Proof(self, indent.to_string())];
+    while let Some(print) = stack.pop() {
+      let (proof, indent) = match print {
+        Print::Proof(proof, indent) => (proof, indent),
+        Print::Text(text) => {
+          tree.push_str(&text);
+          continue;
+        }
+      };
+
+      let marker = match proof.label_tex() {
+        Some(label) => format!("[{}]", label),
+        None => String::new(),
+      };
+
+      match proof.step {
+        Step::Assumption(label) => {
+          tree.push_str(&format!(
+            "{}[{}]_{{{}}}\n",
+            indent,
+            proof.conclusion.tex(),
+            label
+          ));
+        }
+        Step::Premise => {
+          tree.push_str(&format!("{}{}\n", indent, proof.conclusion.tex()));
+        }
+        Step::Inference { ref premises, .. } if premises.is_empty() => {
+          tree.push_str(&format!(
+            "{}\\infer{}{{{}}}{{}}\n",
+            indent,
+            marker,
+            proof.conclusion.tex()
+          ));
+        }
+        Step::Inference { ref premises, .. } => {
+          tree.push_str(&format!(
+            "{}\\infer{}{{{}}}{{\n",
+            indent,
+            marker,
+            proof.conclusion.tex()
+          ));
+          stack.push(Print::Text(format!("{}}}\n", indent)));
+          for (i, premise) in premises.iter().enumerate().rev() {
+            stack.push(Print::Proof(premise, format!("{}  ", indent)));
+            if i > 0 {
+              stack.push(Print::Text(format!("{}  &\n", indent)));
+            }
+          }
+        }
+      }
+    }
+  }
+
+  /// TeX記法（bussproofs.sty）用の証明図出力を行う関数です．
+  /// bussproofs.styは前提を先に，結論を後に書くため，帰りがけ順に出力します．
+  /// 前提を書き出した後に結論を書き出すため，推論を明示的なスタックに積みます．
+  fn print_bussproofs(&self, tree: &mut String, indent: &str) {
+    let mut stack = vec![(self, indent.to_string(), false)];
+    while let Some((proof, indent, visited)) = stack.pop() {
+      let command = match proof.step {
+        Step::Assumption(label) => {
+          tree.push_str(&format!(
+            "{}\\AxiomC{{$[{}]_{{{}}}$}}\n",
+            indent,
+            proof.conclusion.tex(),
+            label
+          ));
+          continue;
+        }
+        Step::Premise => {
+          tree.push_str(&format!("{}\\AxiomC{{${}$}}\n", indent, proof.conclusion.tex()));
+          continue;
+        }
+        Step::Inference { ref premises, .. } if !visited => {
+          if premises.is_empty() {
+            tree.push_str(&format!("{}  \\AxiomC{{}}\n", indent));
+          }
+          let inner = format!("{}  ", indent);
+          stack.push((proof, indent, true));
+          for premise in premises.iter().rev() {
+            stack.push((premise, inner.clone(), false));
+          }
+          continue;
+        }
+        Step::Inference { ref premises, .. } => match premises.len() {
+          0 | 1 => "UnaryInfC",
+          2 => "BinaryInfC",
+          _ => "TrinaryInfC",
+        },
+      };
+
+      if let Some(label) = proof.label_tex() {
+        tree.push_str(&format!("{}\\RightLabel{{${}$}}\n", indent, label));
+      }
+      tree.push_str(&format!(
+        "{}\\{}{{${}$}}\n",
+        indent,
+        command,
+        proof.conclusion.tex()
+      ));
+    }
+  }
+
+  /// TeX記法（bussproofs.sty）で証明図を出力します．`prooftree`環境で囲まれます．
+  pub fn bussproofs(&self) -> String {
+    let mut tree = String::from("\\begin{prooftree}\n");
+    self.print_bussproofs(&mut tree, "  ");
+    tree.push_str("\\end{prooftree}\n");
+    tree
+  }
+}
+
+/// TeX記法の証明図の出力の途中で，これから書き出すものです．
+enum Print<'a> {
+  /// 与えられた字下げで書き出す推論です．
+  Proof(&'a Proof, String),
+
+  /// そのまま書き出す文字列です．
+  Text(String),
+}
+
+impl Drop for Proof {
+  /// 深い証明図でもスタックを溢れさせないよう，前提を明示的なスタックに移してから破棄します．
+  fn drop(&mut self) {
+    let mut stack = match self.step {
+      Step::Inference { ref mut premises, .. } => std::mem::take(premises),
+      _ => return,
+    };
+    while let Some(mut proof) = stack.pop() {
+      if let Step::Inference { ref mut premises, .. } = proof.step {
+        stack.append(premises);
+      }
+    }
+  }
+}
+
+impl TeX for Proof {
+  fn tex(&self) -> String {
+    let mut tree = String::new();
+    self.print_tex(&mut tree, "");
+    tree
+  }
+}
+
+impl Display for Proof {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut tree = String::new();
+    self.print(&mut tree, "");
+    write!(f, "{}", tree)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+  use crate::solver::Classical;
+
+  #[test]
+  fn test_owned() {
+    fn assert_owned<T: Send + Sync + 'static>(_: &T) {}
+
+    let proof = {
+      let logic = Logic::new("A and B to B").unwrap();
+      let inference = logic.solve(None).unwrap();
+      inference.to_proof()
+    };
+    assert_owned(&proof);
+
+    let handle = std::thread::spawn(move || proof.to_string());
+    assert_eq!(
+      handle.join().unwrap(),
+      "A ∧ B → B [→I] : 1\n+ B [∧E]\n  + A ∧ B from: 1\n"
+    );
+  }
+
+  #[test]
+  fn test_parse() {
+    for s in [
+      "((A or B) to C) to (A to C) and (B to C)",
+      "(A iff B) and (B iff C) to (A iff C)",
+      "((A to B) to A) to A",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      let proof = logic.solve(Some(Classical::ExcludedMiddle)).unwrap().to_proof();
+      assert_eq!(proof.to_string().parse(), Ok(proof), "{}", s);
+    }
+
+    let proof: Proof = "A ∧ B → B [toI] : 1\n+ B [\\land E]\n\n  + A and B from: 1\n"
+      .parse()
+      .unwrap();
+    assert_eq!(proof.to_string(), "A ∧ B → B [→I] : 1\n+ B [∧E]\n  + A ∧ B from: 1\n");
+
+    let err = "A ∧ B → B [→I] : 1\n+ B [∧F]".parse::<Proof>().unwrap_err();
+    assert_eq!((err.line, err.column, err.expected), (2, 6, vec!["rule"]));
+
+    let err = "B [→E]\n+ A\n  + A → B".parse::<Proof>().unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "line 3, column 1: expected shallower indentation\n  + A → B\n^"
+    );
+
+    let err = "A → A [→I] 1".parse::<Proof>().unwrap_err();
+    assert_eq!(err.expected, vec!["`:`", "end of line"]);
+
+    let err = "A from 1".parse::<Proof>().unwrap_err();
+    assert_eq!(
+      err.expected,
+      vec!["operator", "`[`", "`from:`", "end of line"]
+    );
+  }
+
+  #[test]
+  fn test_shared() {
+    let logic = Logic::new("A to A").unwrap();
+    let proof = logic.solve(None).unwrap().to_proof();
+    assert_eq!(proof.label(), Some(1));
+    assert_eq!(proof.premises()[0].step(), &Step::Assumption(1));
+
+    let logic = Logic::new("A to A and A").unwrap();
+    let proof = logic.solve(None).unwrap().to_proof();
+    let premises = proof.premises()[0].premises();
+    assert!(Arc::ptr_eq(
+      premises[0].conclusion_arc(),
+      premises[1].conclusion_arc()
+    ));
+  }
+}
@@ -5,433 +5,1696 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::Display;
 use std::rc::*;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::vec::IntoIter;
 
-use super::{logic::*, TeX};
+use super::{arena::*, budget::*, checker::ProofError, hilbert::Hilbert, kripke::Kripke, logic::*, proof::*, TeX};
 
-/// 推論を示す構造です．木構造のノードです．仮定以外では証明図の横線と一対一対応します．
+/// 推論すべき問題を示す構造です．証明探索における，仮定の列と目的の論理式の組です．
+/// 論理式は[Arena]に格納し，その番号で持ちます．
 #[derive(Debug, Clone)]
-pub struct Problem<'a> {
+pub struct Problem {
   /// 推論されるべき論理です．
-  logic: &'a Logic,
+  logic: FormulaId,
 
-  /// この推論に用いることができる仮定の集合です．key-valueペアのkeyが仮定された論理式で，
-  /// valueはその仮定が導出された[Inference](self::Inference)の[self::Inference]です．
-  axioms: HashMap<&'a Logic, Rc<RefCell<usize>>>,
+  /// この推論に用いることができる仮定の列です．導入された順に並び，各要素はその仮定を導く推論です．
+  /// 閉じられる仮定そのものは[InferenceType::Axiom]，推件の前提は[InferenceType::Premise]で，
+  /// 仮定を分解して得られた論理式はそれらから推論されます．
+  axioms: Vec<Inference>,
 
-  /// 推論を一意に示すためのマーカーです．
-  /// 仮定を用いるときに参照番号を付けるために利用します．
-  marker: Rc<RefCell<usize>>,
+  /// 用いる古典論理の規則です．[None]であれば直観主義論理で推論します．
+  classical: Option<Classical>,
+
+  /// 探索全体で共有する資源の制限と，その消費量です．
+  budget: Rc<Budget>,
+
+  /// 論理式を格納するアリーナです．探索全体と，得られた推論で共有します．
+  arena: Rc<RefCell<Arena>>,
+}
+
+/// 探索の途中で推論に失敗した理由を示す列挙子です．[Problem::solve]で[SolveError]に変換されます．
+enum Failure {
+  /// 探索が行き詰まりました．行き詰まった問題の目的の論理式と仮定，試みて失敗した推論規則とその対象の論理式を持ちます．
+  Stuck(FormulaId, Vec<FormulaId>, Vec<(Rule, FormulaId)>),
+
+  /// 資源の制限に達しました．
+  Exhausted(Resource),
+}
+
+/// 探索の途中で推論を試みた結果です．
+type Search = Result<Inference, Failure>;
 
-  /// 解こうとしている問題の列です．
-  /// ループを検知するために利用します．
-  history: Vec<Self>,
+/// 証明探索で次に行うことを示す列挙子です．
+enum Next {
+  /// 問題の推論を試みます．
+  Prove(Problem),
+
+  /// 推論を試みた結果を，スタックの一番上で待っている[Frame]に返します．
+  Return(Search),
 }
 
-impl PartialEq for Problem<'_> {
-  fn eq(&self, other: &Self) -> bool {
-    self.logic == other.logic && self.axioms == other.axioms
+/// 部分問題を推論した結果を待っている，探索の途中の推論を示す列挙子です．
+/// 再帰呼び出しの代わりにこれを明示的なスタックに積むため，深く入れ子になった論理式でもスタックを溢れさせません．
+enum Frame {
+  /// 探索の深さを1つ深くしています．結果を受け取ると深さを戻し，そのまま返します．
+  Leave(Depth),
+
+  /// 古典論理の問題を直観主義論理で探索しています．失敗すれば，問題を分解するか古典論理の規則を用います．
+  Classical(Problem),
+
+  /// 部分問題`rest`を順に推論しています．すべて推論できれば，それらを`premises`に続けて前提とし，
+  /// `problem`の論理式を推論規則`rule`で推論します．
+  Infer {
+    problem: Problem,
+    marker: Rc<RefCell<usize>>,
+    rule: Rule,
+    premises: Vec<Inference>,
+    rest: IntoIter<Problem>,
+  },
+
+  /// 論理和の導入を試みています．失敗すれば，残りの命題の推論を順に試みます．
+  Or(Problem, IntoIter<FormulaId>),
+
+  /// 可逆でない規則`attempt`を試みています．失敗すれば`attempts`に加え，`next`番目以降の仮定を用いる規則を試みます．
+  /// 資源の制限に達した場合は，他の規則も試みずに探索を打ち切ります．
+  Attempt {
+    problem: Problem,
+    attempts: Vec<(Rule, FormulaId)>,
+    attempt: (Rule, FormulaId),
+    next: usize,
+  },
+
+  /// `(A → B) → C`または`¬A → C`の形の仮定の前件を推論しています．
+  /// 推論できれば，それを用いて`C`を仮定に加え，目的の問題の推論を試みます．
+  Nested(Inference, Problem),
+}
+
+impl Frame {
+  /// 部分問題を推論した結果`result`を受け取り，探索を続けます．
+  fn resume(self, result: Search, stack: &mut Vec<Self>) -> Next {
+    match (self, result) {
+      (Self::Leave(depth), result) => {
+        drop(depth);
+        Next::Return(result)
+      }
+      (Self::Classical(problem), Err(err @ Failure::Stuck(..))) => match node(&problem.arena, problem.logic) {
+        Formula::Not(_) | Formula::And(_, _) | Formula::To(_, _) | Formula::Iff(_, _) => problem.infer_logic(stack),
+        Formula::Cont => Next::Return(Err(err)),
+        _ => problem.infer_classical(stack),
+      },
+      (
+        Self::Infer {
+          problem,
+          marker,
+          rule,
+          mut premises,
+          rest,
+        },
+        Ok(i),
+      ) => {
+        premises.push(i);
+        problem.infer_all(marker, rule, premises, rest, stack)
+      }
+      (Self::Or(problem, _), Ok(i)) => Next::Return(Ok(problem.infer(new_marker(), Rule::OrIntro, vec![i]))),
+      (Self::Or(problem, rest), Err(Failure::Stuck(..))) => problem.infer_or(rest, stack),
+      (
+        Self::Attempt {
+          problem,
+          mut attempts,
+          attempt,
+          next,
+        },
+        Err(Failure::Stuck(..)),
+      ) => {
+        attempts.push(attempt);
+        problem.attempt_nested(attempts, next, stack)
+      }
+      (Self::Nested(axiom, mut target), Ok(i)) => {
+        target.assume(axiom.eliminate(i));
+        Next::Prove(target)
+      }
+      (_, result) => Next::Return(result),
+    }
   }
 }
 
 /// 推論を示す構造です．木構造のノードです．仮定以外では証明図の横線と一対一対応します．
-#[derive(Debug, Clone)]
-pub struct Inference<'a> {
-  /// 推論されるべき論理です．
-  logic: &'a Logic,
-
-  /// この推論に用いることができる仮定の集合です．key-valueペアのkeyが仮定された論理式で，
-  /// valueはその仮定が導出された[Inference](self::Inference)の[self::Inference]です．
-  axioms: HashMap<&'a Logic, Rc<RefCell<usize>>>,
+///
+/// 複製と破棄は，深い証明図でもスタックを溢れさせないよう，木を再帰せずに明示的なスタックでたどります．
+#[derive(Debug)]
+pub struct Inference {
+  /// 推論された論理です．
+  logic: FormulaId,
 
   /// 推論を一意に示すためのマーカーです．
   /// 仮定を用いるときに参照番号を付けるために利用します．
   marker: Rc<RefCell<usize>>,
 
-  /// 解こうとしている問題の列です．
-  /// ループを検知するために利用します．
-  history: Vec<Problem<'a>>,
-  
   /// 推論のタイプです．
   /// 詳しくは[InferenceType](InferenceType)の説明を参照してください．
-  inference: InferenceType<'a>,
+  inference: InferenceType,
+
+  /// 論理式を格納するアリーナです．同じ探索で得られた推論で共有します．
+  arena: Rc<RefCell<Arena>>,
 }
 
-/// 推論のタイプを示す列挙子です．
-#[derive(Debug, Clone)]
-enum InferenceType<'a> {
+/// 推論のタイプを示す列挙子です．仮定以外では，用いた推論規則を持ちます．
+#[derive(Debug)]
+enum InferenceType {
   /// 仮定です．
   Axiom(Weak<RefCell<usize>>),
 
-  /// 1つの命題から推論するタイプです．論理包含の導入などで用いられます．
-  UnaryInf(Box<Inference<'a>>),
+  /// 推件の前提として与えられた，閉じられない仮定です．
+  Premise,
+
+  /// 推論規則による推論です．前提となる推論を，証明図で左にあるものから順に持ちます．
+  Rule(Rule, Vec<Inference>),
+}
+
+/// 自然演繹の推論規則を示す列挙子です．証明図の横線に添えて表示されます．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+  /// 否定の導入（¬I）です．
+  NotIntro,
+
+  /// 否定の除去（¬E）です．
+  NotElim,
+
+  /// 論理積の導入（∧I）です．
+  AndIntro,
+
+  /// 論理積の除去（∧E）です．
+  AndElim,
+
+  /// 論理和の導入（∨I）です．
+  OrIntro,
+
+  /// 論理和の除去（∨E）です．
+  OrElim,
+
+  /// 論理包含の導入（→I）です．
+  ToIntro,
+
+  /// 論理包含の除去（→E）です．
+  ToElim,
+
+  /// 同値の導入（↔I）です．
+  IffIntro,
 
-  /// 2つの命題から推論するタイプです．論理積の導入などで用いられます．
-  BinaryInf(Box<Inference<'a>>, Box<Inference<'a>>),
+  /// 同値の除去（↔E）です．
+  IffElim,
 
-  /// 3つの命題から推論するタイプです．論理和の消去で用いられます．
-  TrinaryInf(Box<Inference<'a>>, Box<Inference<'a>>, Box<Inference<'a>>),
+  /// 矛盾の除去（⊥E）です．
+  ContElim,
+
+  /// 排中律（LEM）です．
+  ExcludedMiddle,
+
+  /// 二重否定の除去（DNE）です．
+  DoubleNegation,
+
+  /// 背理法（RAA）です．
+  Reductio,
 }
 
-impl<'a> Problem<'a> {
-  /// 新しい推論すべき問題を生成します．
-  pub fn new(logic: &'a Logic) -> Self {
-    Self {
-      logic,
-      axioms: HashMap::new(),
-      marker: Rc::new(RefCell::new(0)),
-      history: Vec::new(),
+impl Rule {
+  /// この推論規則がとる前提の数です．
+  pub fn arity(&self) -> usize {
+    match self {
+      Self::ExcludedMiddle => 0,
+      Self::NotIntro
+      | Self::AndElim
+      | Self::OrIntro
+      | Self::ToIntro
+      | Self::ContElim
+      | Self::DoubleNegation
+      | Self::Reductio => 1,
+      Self::NotElim | Self::AndIntro | Self::ToElim | Self::IffIntro | Self::IffElim => 2,
+      Self::OrElim => 3,
     }
   }
 
-  /// 自分の卑属で推論すべき問題を生成します．
-  fn problem(&self, logic: &'a Logic, insert: Option<(&'a Logic, Rc<RefCell<usize>>)>) -> Self {
-    let mut axioms = self.axioms.clone();
-    if let Some((k, v)) = insert {
-      axioms.insert(k, v);
+  /// この推論規則が古典論理で推論するために追加する規則であれば，それを返します．
+  pub fn classical(&self) -> Option<Classical> {
+    match self {
+      Self::ExcludedMiddle => Some(Classical::ExcludedMiddle),
+      Self::DoubleNegation => Some(Classical::DoubleNegation),
+      Self::Reductio => Some(Classical::Reductio),
+      _ => None,
     }
+  }
+}
 
-    let mut history = self.history.clone();
-    history.push(self.clone());
+impl TeX for Rule {
+  fn tex(&self) -> String {
+    match self {
+      Self::NotIntro => "\\lnot I",
+      Self::NotElim => "\\lnot E",
+      Self::AndIntro => "\\land I",
+      Self::AndElim => "\\land E",
+      Self::OrIntro => "\\lor I",
+      Self::OrElim => "\\lor E",
+      Self::ToIntro => "\\to I",
+      Self::ToElim => "\\to E",
+      Self::IffIntro => "\\leftrightarrow I",
+      Self::IffElim => "\\leftrightarrow E",
+      Self::ContElim => "\\perp E",
+      Self::ExcludedMiddle => "\\mathrm{LEM}",
+      Self::DoubleNegation => "\\mathrm{DNE}",
+      Self::Reductio => "\\mathrm{RAA}",
+    }
+    .to_string()
+  }
+}
 
-    Self {
-      logic,
-      axioms,
-      marker: Rc::new(RefCell::new(0)),
-      history,
+impl Display for Rule {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let name = match self {
+      Self::NotIntro => "¬I",
+      Self::NotElim => "¬E",
+      Self::AndIntro => "∧I",
+      Self::AndElim => "∧E",
+      Self::OrIntro => "∨I",
+      Self::OrElim => "∨E",
+      Self::ToIntro => "→I",
+      Self::ToElim => "→E",
+      Self::IffIntro => "↔I",
+      Self::IffElim => "↔E",
+      Self::ContElim => "⊥E",
+      Self::ExcludedMiddle => "LEM",
+      Self::DoubleNegation => "DNE",
+      Self::Reductio => "RAA",
+    };
+    write!(f, "{}", name)
+  }
+}
+
+impl FromStr for Rule {
+  type Err = String;
+  /// 推論規則の名前をパースします．標準出力の表記（`→I`），TeX記法（`\to I`），
+  /// 入力用の英字の表記（`toI`，`lem`）を受け付け，空白は無視します．
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let name: String = s.split_whitespace().collect();
+    match name.as_str() {
+      "¬I" | "\\lnotI" | "notI" => Ok(Self::NotIntro),
+      "¬E" | "\\lnotE" | "notE" => Ok(Self::NotElim),
+      "∧I" | "\\landI" | "andI" => Ok(Self::AndIntro),
+      "∧E" | "\\landE" | "andE" => Ok(Self::AndElim),
+      "∨I" | "\\lorI" | "orI" => Ok(Self::OrIntro),
+      "∨E" | "\\lorE" | "orE" => Ok(Self::OrElim),
+      "→I" | "\\toI" | "toI" => Ok(Self::ToIntro),
+      "→E" | "\\toE" | "toE" => Ok(Self::ToElim),
+      "↔I" | "\\leftrightarrowI" | "\\iffI" | "iffI" => Ok(Self::IffIntro),
+      "↔E" | "\\leftrightarrowE" | "\\iffE" | "iffE" => Ok(Self::IffElim),
+      "⊥E" | "\\perpE" | "contE" => Ok(Self::ContElim),
+      "LEM" | "\\mathrm{LEM}" | "lem" => Ok(Self::ExcludedMiddle),
+      "DNE" | "\\mathrm{DNE}" | "dne" => Ok(Self::DoubleNegation),
+      "RAA" | "\\mathrm{RAA}" | "raa" => Ok(Self::Reductio),
+      _ => Err(format!(
+        "unknown rule: {} (expected ¬I, ¬E, ∧I, ∧E, ∨I, ∨E, →I, →E, ↔I, ↔E, ⊥E, LEM, DNE or RAA)",
+        s
+      )),
+    }
+  }
+}
+
+/// 古典論理で推論するために追加する規則を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classical {
+  /// 排中律です．`A ∨ ¬A`を前提なしに導き，論理和の除去を行います．
+  ExcludedMiddle,
+
+  /// 二重否定の除去です．`¬¬A`から`A`を推論します．
+  DoubleNegation,
+
+  /// 背理法です．`¬A`を仮定して矛盾を導き，`A`を推論します．
+  Reductio,
+}
+
+impl FromStr for Classical {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "lem" => Ok(Self::ExcludedMiddle),
+      "dne" => Ok(Self::DoubleNegation),
+      "raa" => Ok(Self::Reductio),
+      _ => Err(format!("unknown classical rule: {} (expected lem, dne or raa)", s)),
     }
   }
+}
 
-  /// 自分の推論が得られたとき，自分を推論にアップグレードします．
-  fn infer(self, inference: InferenceType<'a>) -> Inference<'a> {
-    let Self {
+/// アリーナに格納された論理式のノードを取り出します．借用はすぐに返すため，続けて論理式を格納できます．
+fn node(arena: &RefCell<Arena>, id: FormulaId) -> Formula {
+  arena.borrow()[id].clone()
+}
+
+/// ノードをアリーナに格納し，その番号を返します．
+fn intern(arena: &RefCell<Arena>, formula: Formula) -> FormulaId {
+  arena.borrow_mut().intern(formula)
+}
+
+/// 論理式の否定を格納し，その番号を返します．
+fn negate(arena: &RefCell<Arena>, logic: FormulaId) -> FormulaId {
+  intern(arena, Formula::Not(logic))
+}
+
+/// `left`を仮定して`right`を導く論理式を格納し，その番号を返します．`right`が矛盾であれば`left`の否定になります．
+fn implies(arena: &RefCell<Arena>, left: FormulaId, right: FormulaId) -> FormulaId {
+  match node(arena, right) {
+    Formula::Cont => negate(arena, left),
+    _ => intern(arena, Formula::To(left, right)),
+  }
+}
+
+/// 推論を一意に示すマーカーを生成します．
+fn new_marker() -> Rc<RefCell<usize>> {
+  Rc::new(RefCell::new(0))
+}
+
+impl Problem {
+  /// 新しい推論すべき問題を生成します．`premises`は閉じられない仮定として用いられます．
+  /// `classical`が[Some]であれば，その規則を加えた古典論理で推論します．
+  pub fn new(logic: &Logic, premises: &[Logic], classical: Option<Classical>) -> Self {
+    let mut arena = Arena::new();
+    let logic = arena.insert(logic);
+    let premises: Vec<_> = premises.iter().map(|premise| arena.insert(premise)).collect();
+    Self::in_arena(Rc::new(RefCell::new(arena)), logic, &premises, classical)
+  }
+
+  /// アリーナ`arena`に格納された論理式から，新しい推論すべき問題を生成します．
+  /// 複数の問題で同じアリーナを共有すれば，共通する部分式は一度だけ格納されます．
+  pub fn in_arena(
+    arena: Rc<RefCell<Arena>>,
+    logic: FormulaId,
+    premises: &[FormulaId],
+    classical: Option<Classical>,
+  ) -> Self {
+    let mut problem = Self {
       logic,
-      axioms,
-      marker,
-      history
-    } = self;
-    Inference {
+      axioms: Vec::new(),
+      classical,
+      budget: Rc::new(Budget::new(Limits::default())),
+      arena,
+    };
+    for &premise in premises {
+      let premise = Inference::premise(premise, &problem.arena);
+      problem.assume(premise);
+    }
+    problem
+  }
+
+  /// 探索に用いる資源を`limits`で制限します．経過時間は，この呼び出しから数えます．
+  pub fn limit(mut self, limits: Limits) -> Self {
+    self.budget = Rc::new(Budget::new(limits));
+    self
+  }
+
+  /// 自分と同じ仮定から推論すべき問題を生成します．`axiom`があれば仮定に加えます．
+  fn problem(&self, logic: FormulaId, axiom: Option<Inference>) -> Self {
+    let mut problem = Self {
       logic,
-      axioms,
-      marker,
-      history,
-      inference,
+      ..self.clone()
+    };
+    if let Some(axiom) = axiom {
+      problem.assume(axiom);
     }
+    problem
+  }
+
+  /// 仮定を加えます．同じ論理式が既に仮定されていれば加えません．
+  fn assume(&mut self, axiom: Inference) {
+    if self.axioms.iter().all(|a| a.logic != axiom.logic) {
+      self.axioms.push(axiom);
+    }
+  }
+
+  /// 自分の論理式を推論規則`rule`で`premises`から推論します．
+  fn infer(self, marker: Rc<RefCell<usize>>, rule: Rule, premises: Vec<Inference>) -> Inference {
+    Inference::new(self.logic, marker, rule, premises, &self.arena)
+  }
+
+  /// 矛盾を格納し，その番号を返します．
+  fn cont(&self) -> FormulaId {
+    intern(&self.arena, Formula::Cont)
   }
 
-  fn err(&self) -> SolveResult<'a> {
-    Err(SolveError { logic: self.logic.clone() })
+  /// 自分の推論に行き詰まったことを示すエラーを返します．`attempts`は試みて失敗した推論規則と，その対象の論理式です．
+  fn err(&self, attempts: Vec<(Rule, FormulaId)>) -> Search {
+    let assumptions = self.axioms.iter().map(|a| a.logic).collect();
+    Err(Failure::Stuck(self.logic, assumptions, attempts))
   }
 
   /// 自分の推論を試みます．
-  pub fn solve(self) -> SolveResult<'a> {
-    if let Some(_) = self.history.iter().find(|&p| p == &self) {
-      return self.err();
+  ///
+  /// 直観主義論理では，DyckhoffのLJT（G4ip）に従って探索します．
+  /// 仮定はそれを導く推論とともに持ち，左規則は仮定から新たな仮定を推論することで，
+  /// 右規則は導入規則で自然演繹の証明図を組み立てます．この探索は必ず停止し，
+  /// 失敗すればその問題は直観主義論理上証明不可能です．
+  ///
+  /// 古典論理では，直観主義論理で証明できなければ導入規則で問題を分解し，
+  /// 原子式，論理和，矛盾に行き着いたところで古典論理の規則を用います．
+  ///
+  /// 直観主義論理で失敗すれば，その反例となるクリプキモデルを[SolveError]に添えます．
+  /// [Problem::limit]で設定した資源の制限に達すれば，[SolveError::ResourceExhausted]を返します．
+  /// 反例の探索も，証明探索と同じ資源の制限に従います．
+  pub fn solve(self) -> SolveResult {
+    let premises: Vec<FormulaId> = self.axioms.iter().map(|a| a.logic).collect();
+    let conclusion = self.logic;
+    let classical = self.classical;
+    let arena = self.arena.clone();
+    let budget = self.budget.clone();
+    let (logic, assumptions, attempts) = match self.run() {
+      Ok(inference) => return Ok(inference),
+      Err(Failure::Stuck(logic, assumptions, attempts)) => (logic, assumptions, attempts),
+      Err(Failure::Exhausted(resource)) => return Err(SolveError::ResourceExhausted(resource)),
+    };
+
+    let arena = arena.borrow();
+    let countermodel = match classical {
+      Some(_) => None,
+      None => {
+        let premises: Vec<Logic> = premises.into_iter().map(|id| arena.logic(id)).collect();
+        Kripke::find(&premises, &arena.logic(conclusion), &budget).map_err(SolveError::ResourceExhausted)?
+      }
+    };
+    Err(SolveError::Unprovable(Stuck {
+      logic: arena.logic(logic),
+      assumptions: assumptions.into_iter().map(|id| arena.logic(id)).collect(),
+      attempts: attempts
+        .into_iter()
+        .map(|(rule, id)| (rule, arena.logic(id)))
+        .collect(),
+      countermodel,
+    }))
+  }
+
+  /// 自分の推論を試みます．部分問題の推論を待つ推論は，再帰呼び出しの代わりに[Frame]のスタックに積みます．
+  fn run(self) -> Search {
+    let mut stack = vec![];
+    let mut next = Next::Prove(self);
+    loop {
+      next = match next {
+        Next::Prove(problem) => problem.prove(&mut stack),
+        Next::Return(result) => match stack.pop() {
+          Some(frame) => frame.resume(result, &mut stack),
+          None => return result,
+        },
+      };
     }
+  }
 
-    if let Ok(i) = self.clone().infer_logic() {
-      return Ok(i);
+  /// 自分の推論を始めます．探索の途中の問題はこちらから始まります．
+  fn prove(self, stack: &mut Vec<Frame>) -> Next {
+    match self.budget.enter() {
+      Ok(depth) => stack.push(Frame::Leave(depth)),
+      Err(resource) => return Next::Return(Err(Failure::Exhausted(resource))),
+    }
+    if self.classical.is_none() {
+      return self.search(stack);
     }
 
-    if let Ok(i) = self.clone().use_axioms() {
-      return Ok(i);
+    let intuitionistic = Self {
+      classical: None,
+      ..self.clone()
+    };
+    stack.push(Frame::Classical(self));
+    intuitionistic.search(stack)
+  }
+
+  /// 直観主義論理で自分の推論を探索します．
+  fn search(mut self, stack: &mut Vec<Frame>) -> Next {
+    if let Some(axiom) = self.axioms.iter().find(|a| a.logic == self.logic) {
+      return Next::Return(Ok(axiom.clone()));
+    }
+    let cont = self.cont();
+    if let Some(axiom) = self.axioms.iter().find(|a| a.logic == cont) {
+      let axiom = axiom.clone();
+      return Next::Return(Ok(self.infer(new_marker(), Rule::ContElim, vec![axiom])));
+    }
+
+    let formula = node(&self.arena, self.logic);
+    if let Formula::Not(_) | Formula::And(_, _) | Formula::To(_, _) | Formula::Iff(_, _) = formula {
+      return self.infer_logic(stack);
     }
 
-    self.err()
+    if let Some(i) = (0..self.axioms.len()).find(|&i| self.axioms[i].is_invertible(&self.axioms)) {
+      let axiom = self.axioms.remove(i);
+      return axiom.use_invertible(self, stack);
+    }
+
+    // 可逆でない規則を順に試み，すべて失敗すればここで行き詰まったことを報告します．
+    if let Formula::Or(left, right) = formula {
+      stack.push(Frame::Attempt {
+        problem: self.clone(),
+        attempts: vec![],
+        attempt: (Rule::OrIntro, self.logic),
+        next: 0,
+      });
+      return self.infer_or(vec![left, right].into_iter(), stack);
+    }
+    self.attempt_nested(vec![], 0, stack)
   }
 
-  /// 自分が使える仮定から自分の推論を試みます．
-  fn use_axioms(&self) -> SolveResult<'a> {
-    let axioms = self.axioms.clone();
+  /// `next`番目以降の`(A → B) → C`，`¬A → C`等の形の仮定を用いる規則を順に試みます．
+  /// すべて失敗すれば，試みて失敗した`attempts`とともに行き詰まったことを報告します．
+  fn attempt_nested(self, attempts: Vec<(Rule, FormulaId)>, next: usize, stack: &mut Vec<Frame>) -> Next {
+    let i = match (next..self.axioms.len()).find(|&i| self.axioms[i].is_nested()) {
+      Some(i) => i,
+      None => return Next::Return(self.err(attempts)),
+    };
 
-    for (axiom, marker) in axioms {
-      let i = self
-        .problem(axiom, None)
-        .infer(InferenceType::Axiom(Rc::downgrade(&marker)));
-      if let Ok(i) = i.use_logic(self.clone()) {
-        return Ok(i);
+    let mut target = self.clone();
+    let axiom = target.axioms.remove(i);
+    let rule = match node(&self.arena, axiom.logic) {
+      Formula::Not(_) => Rule::NotElim,
+      _ => Rule::ToElim,
+    };
+    stack.push(Frame::Attempt {
+      problem: self,
+      attempts,
+      attempt: (rule, axiom.logic),
+      next: i + 1,
+    });
+    axiom.use_nested(target, stack)
+  }
+
+  /// `problems`の推論を順に試み，すべて推論できれば，それらを`premises`に続けて前提とし，
+  /// 自分の論理式を推論規則`rule`で推論します．
+  fn infer_all(
+    self,
+    marker: Rc<RefCell<usize>>,
+    rule: Rule,
+    premises: Vec<Inference>,
+    mut problems: IntoIter<Self>,
+    stack: &mut Vec<Frame>,
+  ) -> Next {
+    match problems.next() {
+      Some(p) => {
+        stack.push(Frame::Infer {
+          problem: self,
+          marker,
+          rule,
+          premises,
+          rest: problems,
+        });
+        Next::Prove(p)
       }
+      None => Next::Return(Ok(self.infer(marker, rule, premises))),
     }
-
-    self.err()
   }
 
   /// 自分の論理式の木の根の演算子を導入し，推論を試みます．
-  fn infer_logic(self) -> SolveResult<'a> {
-    match self.logic {
-      Logic::Not(logic) => self.infer_not(logic),
-      Logic::And(left, right) => self.infer_and(left, right),
-      Logic::Or(left, right) => self.infer_or(left, right),
-      Logic::To(left, right) => self.infer_to(left, right),
-      _ => self.err(),
+  fn infer_logic(self, stack: &mut Vec<Frame>) -> Next {
+    match node(&self.arena, self.logic) {
+      Formula::Not(logic) => self.infer_not(logic, stack),
+      Formula::And(left, right) => self.infer_and(left, right, stack),
+      Formula::Or(left, right) => self.infer_or(vec![left, right].into_iter(), stack),
+      Formula::To(left, right) => self.infer_to(left, right, stack),
+      Formula::Iff(left, right) => self.infer_iff(left, right, stack),
+      _ => Next::Return(self.err(vec![])),
     }
   }
 
   /// 論理否定を導入します．否定されていない命題を仮定し，矛盾の推論を試みます．
-  fn infer_not(self, logic: &'a Logic) -> SolveResult<'a> {
-    let p = self.problem(&Logic::Cont, Some((logic, self.marker.clone())));
-    Ok(self.infer(InferenceType::UnaryInf(Box::new(p.solve()?))))
+  fn infer_not(self, logic: FormulaId, stack: &mut Vec<Frame>) -> Next {
+    let marker = new_marker();
+    let p = self.problem(self.cont(), Some(Inference::axiom(logic, &marker, &self.arena)));
+    self.infer_all(marker, Rule::NotIntro, vec![], vec![p].into_iter(), stack)
   }
 
   /// 論理積を導入するため，2つの命題の推論をそれぞれ試みます．
-  fn infer_and(self, left: &'a Logic, right: &'a Logic) -> SolveResult<'a> {
-    let p0 = self.problem(left, None);
-    let p1 = self.problem(right, None);
-    Ok(self.infer(InferenceType::BinaryInf(
-      Box::new(p0.solve()?),
-      Box::new(p1.solve()?),
-    )))
+  fn infer_and(self, left: FormulaId, right: FormulaId, stack: &mut Vec<Frame>) -> Next {
+    let problems = vec![self.problem(left, None), self.problem(right, None)];
+    self.infer_all(new_marker(), Rule::AndIntro, vec![], problems.into_iter(), stack)
   }
 
-  /// 論理和を導入するため，2つの命題の推論をそれぞれ試みます．
-  fn infer_or(self, left: &'a Logic, right: &'a Logic) -> SolveResult<'a> {
-    for logic in [left, right] {
-      let p = self.problem(logic, None);
-      if let Ok(i) = p.solve() {
-        return Ok(self.infer(InferenceType::UnaryInf(Box::new(i))));
+  /// 論理和を導入するため，左右の命題のうち`rest`に残るものの推論を順に試みます．
+  fn infer_or(self, mut rest: IntoIter<FormulaId>, stack: &mut Vec<Frame>) -> Next {
+    match rest.next() {
+      Some(logic) => {
+        let p = self.problem(logic, None);
+        stack.push(Frame::Or(self, rest));
+        Next::Prove(p)
       }
+      None => Next::Return(self.err(vec![])),
     }
-
-    self.err()
   }
 
   /// 論理包含を導入するため，左の命題を仮定し，右の命題の推論を試みます．
-  fn infer_to(self, left: &'a Logic, right: &'a Logic) -> SolveResult<'a> {
-    let p0 = self.problem(right, Some((left, self.marker.clone())));
-    Ok(self.infer(InferenceType::UnaryInf(Box::new(p0.solve()?))))
+  fn infer_to(self, left: FormulaId, right: FormulaId, stack: &mut Vec<Frame>) -> Next {
+    let marker = new_marker();
+    let p = self.problem(right, Some(Inference::axiom(left, &marker, &self.arena)));
+    self.infer_all(marker, Rule::ToIntro, vec![], vec![p].into_iter(), stack)
+  }
+
+  /// 同値を導入するため，左の命題を仮定した右の命題と，右の命題を仮定した左の命題の推論をそれぞれ試みます．
+  fn infer_iff(self, left: FormulaId, right: FormulaId, stack: &mut Vec<Frame>) -> Next {
+    let marker = new_marker();
+    let p0 = self.problem(right, Some(Inference::axiom(left, &marker, &self.arena)));
+    let p1 = self.problem(left, Some(Inference::axiom(right, &marker, &self.arena)));
+    self.infer_all(marker, Rule::IffIntro, vec![], vec![p0, p1].into_iter(), stack)
+  }
+
+  /// 古典論理の規則を用いて推論を試みます．原子式と論理和にのみ適用します．
+  /// 古典論理上導ける論理式の否定を仮定すれば直観主義論理上矛盾を導けるため（Glivenkoの定理），
+  /// その内側は直観主義論理で推論します．
+  fn infer_classical(self, stack: &mut Vec<Frame>) -> Next {
+    match self.classical {
+      Some(Classical::ExcludedMiddle) => self.infer_lem(stack),
+      Some(Classical::DoubleNegation) => self.infer_dne(stack),
+      Some(Classical::Reductio) => self.infer_raa(stack),
+      None => Next::Return(self.err(vec![])),
+    }
+  }
+
+  /// 排中律を用います．自分が`A ∨ ¬A`の形であればそのまま推論し，
+  /// そうでなければ`A ∨ ¬A`の論理和の除去によって`A`の推論を試みます．
+  fn infer_lem(self, stack: &mut Vec<Frame>) -> Next {
+    if let Formula::Or(left, right) = node(&self.arena, self.logic) {
+      if right == negate(&self.arena, left) {
+        return Next::Return(Ok(self.infer(new_marker(), Rule::ExcludedMiddle, vec![])));
+      }
+    }
+
+    let not = negate(&self.arena, self.logic);
+    let lem = intern(&self.arena, Formula::Or(self.logic, not));
+
+    let marker = new_marker();
+    let i0 = Inference::new(lem, new_marker(), Rule::ExcludedMiddle, vec![], &self.arena);
+    let i1 = Inference::axiom(self.logic, &marker, &self.arena);
+    let p2 = Self {
+      classical: None,
+      ..self.problem(self.logic, Some(Inference::axiom(not, &marker, &self.arena)))
+    };
+    self.infer_all(marker, Rule::OrElim, vec![i0, i1], vec![p2].into_iter(), stack)
+  }
+
+  /// 二重否定の除去を用います．`¬¬A`の推論を試みます．
+  fn infer_dne(self, stack: &mut Vec<Frame>) -> Next {
+    let not = negate(&self.arena, self.logic);
+    let p = Self {
+      classical: None,
+      ..self.problem(negate(&self.arena, not), None)
+    };
+    self.infer_all(new_marker(), Rule::DoubleNegation, vec![], vec![p].into_iter(), stack)
+  }
+
+  /// 背理法を用います．`¬A`を仮定し，矛盾の推論を試みます．
+  fn infer_raa(self, stack: &mut Vec<Frame>) -> Next {
+    let not = negate(&self.arena, self.logic);
+    let marker = new_marker();
+    let p = Self {
+      classical: None,
+      ..self.problem(self.cont(), Some(Inference::axiom(not, &marker, &self.arena)))
+    };
+    self.infer_all(marker, Rule::Reductio, vec![], vec![p].into_iter(), stack)
   }
 }
 
-impl Display for Problem<'_> {
+impl Display for Problem {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let arena = self.arena.borrow();
     let mut axioms = String::new();
-    for (axiom, _) in &self.axioms {
-      axioms.push_str(&format!("{}, ", axiom));
+    for axiom in &self.axioms {
+      axioms.push_str(&format!("{}, ", arena.logic(axiom.logic)));
     }
-    write!(f, "Problem{{ logic: {}, axioms: {} }}", self.logic, axioms)
+    write!(f, "Problem{{ logic: {}, axioms: {} }}", arena.logic(self.logic), axioms)
   }
 }
 
-impl<'a> Inference<'a> {
-  /// 自分の卑属で推論すべき問題を生成します．
-  fn problem(
-    &self,
-    logic: &'a Logic,
-    insert: Option<(&'a Logic, Rc<RefCell<usize>>)>,
-  ) -> Problem<'a> {
-    let mut axioms = self.axioms.clone();
-    if let Some((k, v)) = insert {
-      axioms.insert(k, v);
+impl Inference {
+  /// 推論規則`rule`で`premises`から`logic`を推論します．
+  /// `marker`は，この推論で閉じる仮定から参照されます．
+  fn new(
+    logic: FormulaId,
+    marker: Rc<RefCell<usize>>,
+    rule: Rule,
+    premises: Vec<Self>,
+    arena: &Rc<RefCell<Arena>>,
+  ) -> Self {
+    Self {
+      logic,
+      marker,
+      inference: InferenceType::Rule(rule, premises),
+      arena: arena.clone(),
+    }
+  }
+
+  /// `marker`を持つ推論で閉じられる仮定を生成します．
+  fn axiom(logic: FormulaId, marker: &Rc<RefCell<usize>>, arena: &Rc<RefCell<Arena>>) -> Self {
+    Self {
+      logic,
+      marker: new_marker(),
+      inference: InferenceType::Axiom(Rc::downgrade(marker)),
+      arena: arena.clone(),
     }
+  }
 
-    Problem {
+  /// 推件の前提として与えられた，閉じられない仮定を生成します．
+  fn premise(logic: FormulaId, arena: &Rc<RefCell<Arena>>) -> Self {
+    Self {
       logic,
-      axioms,
-      marker: Rc::new(RefCell::new(0)),
-      history: self.history.clone(),
+      marker: new_marker(),
+      inference: InferenceType::Premise,
+      arena: arena.clone(),
     }
   }
 
-  /// 自分が解けなかったというエラーを出力します．
-  fn err(&self) -> SolveResult<'a> {
-    Err(SolveError { logic: self.logic.clone() })
+  /// 自分を用いて，推論規則`rule`で`logic`を推論します．`before`は自分より左に置く前提です．
+  fn then(self, logic: FormulaId, rule: Rule, before: Option<Self>) -> Self {
+    let arena = self.arena.clone();
+    let mut premises: Vec<_> = before.into_iter().collect();
+    premises.push(self);
+    Self::new(logic, new_marker(), rule, premises, &arena)
   }
 
-  /// 得られた推論から目的の問題の推論を試みます．
-  fn use_logic(self, target: Problem<'a>) -> SolveResult<'a> {
-    if self.logic.eq(target.logic) {
-      return Ok(self);
+  /// 自分が`A → B`または`¬A`であるとき，`A`を導く推論`i`を用いて`B`または矛盾を推論します．
+  /// 自分が導入規則で推論されていれば，導入した直後に除去する回り道を避け，閉じた仮定を`i`で置き換えます．
+  fn eliminate(self, i: Self) -> Self {
+    if let InferenceType::Rule(Rule::ToIntro | Rule::NotIntro, ref premises) = self.inference {
+      return premises[0].substitute(&self.marker, &i);
     }
 
-    match self.logic {
-      Logic::Cont => self.use_cont(target),
-      Logic::Not(logic) => self.use_not(target, logic),
-      Logic::And(left, right) => self.use_and(target, left, right),
-      Logic::Or(left, right) => self.use_or(target, left, right),
-      Logic::To(left, right) => self.use_to(target, left, right),
-      _ => self.err(),
+    match node(&self.arena, self.logic) {
+      Formula::To(_, right) => self.then(right, Rule::ToElim, Some(i)),
+      _ => {
+        let cont = intern(&self.arena, Formula::Cont);
+        self.then(cont, Rule::NotElim, Some(i))
+      }
     }
   }
 
-  /// 矛盾を除去し，これを利用して目的の問題を推論します．
-  fn use_cont(self, target: Problem<'a>) -> SolveResult<'a> {
-    Ok(target.infer(InferenceType::UnaryInf(Box::new(self))))
+  /// `marker`を持つ推論で閉じられる仮定を，推論`i`で置き換えた推論を返します．
+  fn substitute(&self, marker: &Rc<RefCell<usize>>, i: &Self) -> Self {
+    self.fold(|node, premises| match node.inference {
+      InferenceType::Axiom(ref m) if Weak::as_ptr(m) == Rc::as_ptr(marker) => i.clone(),
+      InferenceType::Rule(rule, _) => Self {
+        logic: node.logic,
+        marker: node.marker.clone(),
+        inference: InferenceType::Rule(rule, premises),
+        arena: node.arena.clone(),
+      },
+      _ => node.clone(),
+    })
   }
 
-  /// 否定の除去を試み，可能であれば矛盾を推論します．
-  fn use_not(self, target: Problem<'a>, logic: &'a Logic) -> SolveResult<'a> {
-    let p0 = self.problem(logic, None);
-    let p = self.problem(&Logic::Cont, None);
+  /// 証明図を帰りがけ順にたどり，各推論とその前提に`f`を適用した値から，根の値を計算します．
+  fn fold<T>(&self, mut f: impl FnMut(&Self, Vec<T>) -> T) -> T {
+    let mut stack = vec![(self, false)];
+    let mut values = vec![];
+    while let Some((i, visited)) = stack.pop() {
+      let premises = i.premises();
+      if visited {
+        let values_of_premises = values.split_off(values.len() - premises.len());
+        values.push(f(i, values_of_premises));
+      } else {
+        stack.push((i, true));
+        stack.extend(premises.into_iter().rev().map(|premise| (premise, false)));
+      }
+    }
+    values.pop().unwrap()
+  }
 
-    let i = p.infer(InferenceType::BinaryInf(
-      Box::new(p0.solve()?),
-      Box::new(self),
-    ));
-    i.use_logic(target)
+  /// `marker`で閉じられる仮定`left`から自分を導いたとして，`left → 自分`を推論します．
+  /// 自分が矛盾であれば`¬left`を推論します．
+  fn discharge(self, left: FormulaId, marker: Rc<RefCell<usize>>) -> Self {
+    let arena = self.arena.clone();
+    let rule = match node(&arena, self.logic) {
+      Formula::Cont => Rule::NotIntro,
+      _ => Rule::ToIntro,
+    };
+    Self::new(implies(&arena, left, self.logic), marker, rule, vec![self], &arena)
   }
 
-  /// 論理積を除去し，これを用いて目的の問題の推論を試みます．
-  fn use_and(self, target: Problem<'a>, left: &'a Logic, right: &'a Logic) -> SolveResult<'a> {
+  /// 自分の論理式が`A → B`または`¬A`であるとき，`A`を返します．
+  fn antecedent(&self) -> Option<FormulaId> {
+    match node(&self.arena, self.logic) {
+      Formula::To(left, _) | Formula::Not(left) => Some(left),
+      _ => None,
+    }
+  }
+
+  /// 自分を仮定として，可逆な規則で分解できるかを返します．`axioms`は同時に使える仮定です．
+  fn is_invertible(&self, axioms: &[Self]) -> bool {
+    match node(&self.arena, self.logic) {
+      Formula::And(_, _) | Formula::Or(_, _) | Formula::Iff(_, _) => true,
+      _ => match self.antecedent() {
+        Some(antecedent) => match node(&self.arena, antecedent) {
+          Formula::Cont | Formula::And(_, _) | Formula::Or(_, _) | Formula::Iff(_, _) => true,
+          _ => axioms.iter().any(|a| a.logic == antecedent),
+        },
+        None => false,
+      },
+    }
+  }
+
+  /// 自分を仮定として，可逆な規則で分解できない`(A → B) → C`，`¬A → C`等の形であるかを返します．
+  fn is_nested(&self) -> bool {
+    let antecedent = self.antecedent().map(|antecedent| node(&self.arena, antecedent));
+    matches!(antecedent, Some(Formula::Not(_) | Formula::To(_, _)))
+  }
+
+  /// 自分を可逆な規則で分解して新たな仮定とし，目的の問題の推論を試みます．
+  fn use_invertible(self, mut target: Problem, stack: &mut Vec<Frame>) -> Next {
+    let antecedent = self.antecedent();
+    if let Some(i) = target.axioms.iter().find(|a| Some(a.logic) == antecedent) {
+      let i = i.clone();
+      target.assume(self.eliminate(i));
+      return Next::Prove(target);
+    }
+
+    match node(&self.arena, self.logic) {
+      Formula::And(left, right) => self.use_and(target, left, right),
+      Formula::Or(left, right) => self.use_or(target, left, right, stack),
+      Formula::Iff(left, right) => self.use_iff(target, left, right),
+      _ => match antecedent.map(|antecedent| node(&self.arena, antecedent)) {
+        Some(Formula::And(left, right)) => self.use_to_and(target, left, right),
+        Some(Formula::Or(left, right)) => self.use_to_or(target, left, right),
+        Some(Formula::Iff(left, right)) => self.use_to_iff(target, left, right),
+        _ => Next::Prove(target),
+      },
+    }
+  }
+
+  /// 論理積を除去し，左右の命題を仮定に加えて目的の問題の推論を試みます．
+  fn use_and(self, mut target: Problem, left: FormulaId, right: FormulaId) -> Next {
     for logic in [left, right] {
-      let p = self.problem(logic, None);
-      let i = p.infer(InferenceType::UnaryInf(Box::new(self.clone())));
-      if let Ok(i) = i.use_logic(target.clone()) {
-        return Ok(i);
-      }
+      target.assume(Inference::new(
+        logic,
+        new_marker(),
+        Rule::AndElim,
+        vec![self.clone()],
+        &self.arena,
+      ));
     }
+    Next::Prove(target)
+  }
 
-    self.err()
+  /// 論理和を除去するため，左右の命題をそれぞれ仮定して目的の問題の推論を試みます．
+  fn use_or(self, target: Problem, left: FormulaId, right: FormulaId, stack: &mut Vec<Frame>) -> Next {
+    let marker = new_marker();
+    let p1 = target.problem(target.logic, Some(Inference::axiom(left, &marker, &self.arena)));
+    let p2 = target.problem(target.logic, Some(Inference::axiom(right, &marker, &self.arena)));
+    target.infer_all(marker, Rule::OrElim, vec![self], vec![p1, p2].into_iter(), stack)
   }
 
-  /// 論理和の除去を試み，可能であればこれを用いて目的の問題を推論します．
-  fn use_or(self, target: Problem<'a>, left: &'a Logic, right: &'a Logic) -> SolveResult<'a> {
-    let p1 = self.problem(target.logic, Some((left, self.marker.clone())));
-    let p2 = self.problem(target.logic, Some((right, self.marker.clone())));
+  /// 同値を除去して両方向の論理包含を仮定に加え，目的の問題の推論を試みます．
+  fn use_iff(self, mut target: Problem, left: FormulaId, right: FormulaId) -> Next {
+    for (from, to) in [(left, right), (right, left)] {
+      let marker = new_marker();
+      let i = Inference::new(
+        to,
+        new_marker(),
+        Rule::IffElim,
+        vec![Inference::axiom(from, &marker, &self.arena), self.clone()],
+        &self.arena,
+      );
+      target.assume(i.discharge(from, marker));
+    }
+    Next::Prove(target)
+  }
 
-    Ok(target.infer(InferenceType::TrinaryInf(
-      Box::new(self),
-      Box::new(p1.solve()?),
-      Box::new(p2.solve()?),
-    )))
+  /// `A ∧ B → C`を`A → B → C`に書き換えて仮定に加え，目的の問題の推論を試みます．
+  fn use_to_and(self, mut target: Problem, left: FormulaId, right: FormulaId) -> Next {
+    let (m0, m1) = (new_marker(), new_marker());
+    let and = Inference::new(
+      intern(&self.arena, Formula::And(left, right)),
+      new_marker(),
+      Rule::AndIntro,
+      vec![
+        Inference::axiom(left, &m0, &self.arena),
+        Inference::axiom(right, &m1, &self.arena),
+      ],
+      &self.arena,
+    );
+    let i = self.eliminate(and).discharge(right, m1).discharge(left, m0);
+    target.assume(i);
+    Next::Prove(target)
   }
 
-  /// 論理和の除去を試み，可能であればこれを用いて目的の問題の推論を試みます．
-  fn use_to(self, target: Problem<'a>, left: &'a Logic, right: &'a Logic) -> SolveResult<'a> {
-    let p0 = self.problem(left, None);
-    let p = self.problem(right, None);
+  /// `A ∨ B → C`を`A → C`と`B → C`に書き換えて仮定に加え，目的の問題の推論を試みます．
+  fn use_to_or(self, mut target: Problem, left: FormulaId, right: FormulaId) -> Next {
+    let or = intern(&self.arena, Formula::Or(left, right));
+    for logic in [left, right] {
+      let marker = new_marker();
+      let i = Inference::axiom(logic, &marker, &self.arena).then(or, Rule::OrIntro, None);
+      target.assume(self.clone().eliminate(i).discharge(logic, marker));
+    }
+    Next::Prove(target)
+  }
 
-    let i = p.infer(InferenceType::BinaryInf(
-      Box::new(p0.solve()?),
-      Box::new(self),
-    ));
+  /// `(A ↔ B) → C`を`(A → B) → (B → A) → C`に書き換えて仮定に加え，目的の問題の推論を試みます．
+  fn use_to_iff(self, mut target: Problem, left: FormulaId, right: FormulaId) -> Next {
+    let to = implies(&self.arena, left, right);
+    let from = implies(&self.arena, right, left);
+    let (m0, m1, m) = (new_marker(), new_marker(), new_marker());
+    let iff = Inference::new(
+      intern(&self.arena, Formula::Iff(left, right)),
+      m.clone(),
+      Rule::IffIntro,
+      vec![
+        Inference::axiom(to, &m0, &self.arena).eliminate(Inference::axiom(left, &m, &self.arena)),
+        Inference::axiom(from, &m1, &self.arena).eliminate(Inference::axiom(right, &m, &self.arena)),
+      ],
+      &self.arena,
+    );
+    let i = self.eliminate(iff).discharge(from, m1).discharge(to, m0);
+    target.assume(i);
+    Next::Prove(target)
+  }
 
-    i.use_logic(target)
+  /// `(A → B) → C`または`¬A → C`の形の仮定を用います．
+  /// 前者では`B → C`を仮定して`A → B`を，後者では`¬A`を推論し，
+  /// これを用いて`C`を仮定に加え，目的の問題の推論を試みます．
+  fn use_nested(self, target: Problem, stack: &mut Vec<Frame>) -> Next {
+    let antecedent = self.antecedent().unwrap();
+    let mut p = target.problem(antecedent, None);
+    if let Formula::To(_, right) = node(&self.arena, antecedent) {
+      let marker = new_marker();
+      let to = Inference::new(
+        antecedent,
+        new_marker(),
+        Rule::ToIntro,
+        vec![Inference::axiom(right, &marker, &self.arena)],
+        &self.arena,
+      );
+      p.assume(self.clone().eliminate(to).discharge(right, marker));
+    }
+
+    stack.push(Frame::Nested(self, target));
+    Next::Prove(p)
   }
 
-  /// 標準出力用の証明図出力を行う関数です．
-  fn print(&self, tree: &mut String, indent: &str, after: &mut usize) {
-    let marker = if Rc::weak_count(&self.marker) > 0 {
-      *after += 1;
-      self.marker.replace(*after);
-      format!(" : {}", self.marker.borrow())
-    } else {
-      if let InferenceType::Axiom(ref marker) = self.inference {
-        format!(" from: {}", marker.upgrade().unwrap().borrow())
-      } else {
-        String::new()
+  /// 証明図を正規化します．導入した論理式をすぐに除去する回り道をPrawitzの簡約で取り除き，
+  /// 仮定を用いない枝を持つ論理和の除去をその枝に置き換えます．
+  pub fn normalize(self) -> Self {
+    /// 正規化の途中で，これから行うことです．
+    enum Work {
+      /// 推論を正規化します．
+      Visit(Inference),
+
+      /// 正規化した前提から推論を組み立て直し，簡約できれば簡約したものを改めて正規化します．
+      Build(Inference, usize),
+    }
+
+    let mut work = vec![Work::Visit(self)];
+    let mut done: Vec<Self> = vec![];
+    while let Some(next) = work.pop() {
+      match next {
+        Work::Visit(mut i) => {
+          let premises = match i.inference {
+            InferenceType::Rule(_, ref mut premises) => std::mem::take(premises),
+            _ => vec![],
+          };
+          work.push(Work::Build(i, premises.len()));
+          work.extend(premises.into_iter().rev().map(Work::Visit));
+        }
+        Work::Build(mut i, n) => {
+          if let InferenceType::Rule(_, ref mut premises) = i.inference {
+            *premises = done.split_off(done.len() - n);
+          }
+          match i.reduce() {
+            Some(i) => work.push(Work::Visit(i)),
+            None => done.push(i),
+          }
+        }
       }
+    }
+    done.pop().unwrap()
+  }
+
+  /// 自分が回り道であれば，1段階簡約した推論を返します．
+  fn reduce(&self) -> Option<Self> {
+    let (rule, premises) = match self.inference {
+      InferenceType::Rule(rule, ref premises) => (rule, premises.as_slice()),
+      _ => return None,
     };
 
-    tree.push_str(&format!("{}{}\n", self.logic, marker));
-    match self.inference {
-      InferenceType::Axiom(_) => {}
-      InferenceType::UnaryInf(ref i0) => {
-        tree.push_str(&format!("{}+ ", indent));
-        i0.print(tree, &format!("{}  ", indent), after);
-      }
-      InferenceType::BinaryInf(ref i0, ref i1) => {
-        tree.push_str(&format!("{}+ ", indent));
-        i0.print(tree, &format!("{}| ", indent), after);
-        tree.push_str(&format!("{}+ ", indent));
-        i1.print(tree, &format!("{}  ", indent), after);
-      }
-      InferenceType::TrinaryInf(ref i0, ref i1, ref i2) => {
-        tree.push_str(&format!("{}+ ", indent));
-        i0.print(tree, &format!("{}| ", indent), after);
-        tree.push_str(&format!("{}+ ", indent));
-        i1.print(tree, &format!("{}| ", indent), after);
-        tree.push_str(&format!("{}+ ", indent));
-        i2.print(tree, &format!("{}  ", indent), after);
+    // 除去規則の主前提が矛盾の除去であれば，直接矛盾から推論します．
+    let major = match (rule, premises) {
+      (Rule::AndElim, [major]) | (Rule::OrElim, [major, _, _]) => Some(major),
+      (Rule::NotElim | Rule::ToElim | Rule::IffElim, [_, major]) => Some(major),
+      _ => None,
+    };
+    if let Some(InferenceType::Rule(Rule::ContElim, ref cont)) = major.map(|i| &i.inference) {
+      return Some(Self::new(
+        self.logic,
+        new_marker(),
+        Rule::ContElim,
+        cont.clone(),
+        &self.arena,
+      ));
+    }
+
+    match (rule, premises) {
+      (Rule::ContElim, [i0]) if node(&self.arena, self.logic) == Formula::Cont => Some(i0.clone()),
+      (Rule::NotElim | Rule::ToElim, [i0, i1]) => match i1.inference {
+        InferenceType::Rule(Rule::NotIntro | Rule::ToIntro, ref body) => {
+          Some(body[0].substitute(&i1.marker, i0))
+        }
+        _ => None,
+      },
+      (Rule::AndElim, [i0]) => match i0.inference {
+        InferenceType::Rule(Rule::AndIntro, ref parts) => {
+          parts.iter().find(|p| p.logic == self.logic).cloned()
+        }
+        _ => None,
+      },
+      (Rule::IffElim, [i0, i1]) => match i1.inference {
+        InferenceType::Rule(Rule::IffIntro, ref parts) => {
+          let left = node(&self.arena, i1.logic).children()[0];
+          let part = if i0.logic == left { &parts[0] } else { &parts[1] };
+          Some(part.substitute(&i1.marker, i0))
+        }
+        _ => None,
+      },
+      (Rule::OrElim, [i0, i1, i2]) => match i0.inference {
+        InferenceType::Rule(Rule::OrIntro, ref parts) => {
+          let left = node(&self.arena, i0.logic).children()[0];
+          let branch = if parts[0].logic == left { i1 } else { i2 };
+          Some(branch.substitute(&self.marker, &parts[0]))
+        }
+        _ if !i1.uses(&self.marker) => Some(i1.clone()),
+        _ if !i2.uses(&self.marker) => Some(i2.clone()),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  /// `marker`を持つ推論で閉じられる仮定を用いているかを返します．
+  fn uses(&self, marker: &Rc<RefCell<usize>>) -> bool {
+    let mut stack = vec![self];
+    while let Some(i) = stack.pop() {
+      match i.inference {
+        InferenceType::Axiom(ref m) if Weak::as_ptr(m) == Rc::as_ptr(marker) => return true,
+        InferenceType::Rule(_, ref premises) => stack.extend(premises),
+        _ => (),
       }
     }
+    false
   }
 
-  /// TeX記法用の証明図出力を行う関数です．
-  fn print_tex(&self, tree: &mut String, indent: &str, after: &mut usize) {
-    let marker = if Rc::weak_count(&self.marker) > 0 {
-      *after += 1;
-      self.marker.replace(*after);
-      format!("[{}]", self.marker.borrow())
-    } else {
-      String::new()
-    };
+  /// 証明図の大きさです．証明図に現れる論理式の数を数えます．
+  pub fn size(&self) -> usize {
+    self.fold(|_, sizes| 1 + sizes.into_iter().sum::<usize>())
+  }
+
+  /// この推論で導かれる論理式です．アリーナから[Logic]の木に変換して返します．
+  pub fn conclusion(&self) -> Logic {
+    self.arena.borrow().logic(self.logic)
+  }
 
+  /// この推論で導かれる論理式の，アリーナでの番号です．
+  pub fn formula(&self) -> FormulaId {
+    self.logic
+  }
+
+  /// この推論の論理式を格納するアリーナです．
+  pub fn arena(&self) -> &Rc<RefCell<Arena>> {
+    &self.arena
+  }
+
+  /// この推論に用いた推論規則です．仮定であれば[None]です．
+  pub fn rule(&self) -> Option<Rule> {
+    match self.inference {
+      InferenceType::Axiom(_) | InferenceType::Premise => None,
+      InferenceType::Rule(rule, _) => Some(rule),
+    }
+  }
+
+  /// この推論の前提となる推論を，証明図で左にあるものから順に返します．仮定であれば空です．
+  pub fn premises(&self) -> Vec<&Self> {
     match self.inference {
-      InferenceType::Axiom(ref marker) => {
-        tree.push_str(&format!(
-          "{}[{}]_{{{}}}\n",
-          indent,
-          self.logic.tex(),
-          marker.upgrade().unwrap().borrow()
-        ));
+      InferenceType::Axiom(_) | InferenceType::Premise => vec![],
+      InferenceType::Rule(_, ref premises) => premises.iter().collect(),
+    }
+  }
+
+  /// この推論が，いずれかの推論で閉じられる仮定であるかを返します．
+  pub fn is_assumption(&self) -> bool {
+    matches!(self.inference, InferenceType::Axiom(_))
+  }
+
+  /// この推論が，推件の前提として与えられた閉じられない仮定であるかを返します．
+  pub fn is_premise(&self) -> bool {
+    matches!(self.inference, InferenceType::Premise)
+  }
+
+  /// 仮定`leaf`がこの推論で閉じられるかを返します．
+  pub fn discharges(&self, leaf: &Self) -> bool {
+    match leaf.inference {
+      InferenceType::Axiom(ref marker) => marker.ptr_eq(&Rc::downgrade(&self.marker)),
+      _ => false,
+    }
+  }
+
+  /// この推論で閉じられる仮定の論理式を，証明図に現れる順に重複なく返します．
+  pub fn discharged(&self) -> Vec<Logic> {
+    let mut stack = self.premises();
+    stack.reverse();
+    let mut discharged = vec![];
+    while let Some(i) = stack.pop() {
+      if self.discharges(i) && !discharged.contains(&i.logic) {
+        discharged.push(i.logic);
       }
-      InferenceType::UnaryInf(ref i0) => {
-        tree.push_str(&format!(
-          "{}\\infer{}{{{}}}{{\n",
-          indent,
-          marker,
-          self.logic.tex()
-        ));
-        i0.print_tex(tree, &format!("{}  ", indent), after);
-        tree.push_str(&format!("{}}}\n", indent));
+      stack.extend(i.premises().into_iter().rev());
+    }
+    let arena = self.arena.borrow();
+    discharged.into_iter().map(|id| arena.logic(id)).collect()
+  }
+
+  /// 所有された証明図に変換します．仮定の参照番号は，証明図を根から行きがけ順にたどって振られます．
+  /// 同じ論理式は1つの[Arc]を共有します．
+  pub fn to_proof(&self) -> Proof {
+    /// 変換の途中で，これから行うことです．
+    enum Work<'a> {
+      /// 推論を変換します．
+      Visit(&'a Inference),
+
+      /// 変換した前提から，推論規則による推論を組み立てます．
+      Build(Arc<Logic>, Rule, Option<usize>, usize),
+    }
+
+    let arena = self.arena.borrow();
+    let mut after = 0;
+    let mut formulas: HashMap<FormulaId, Arc<Logic>> = HashMap::new();
+    let mut work = vec![Work::Visit(self)];
+    let mut done: Vec<Proof> = vec![];
+    while let Some(next) = work.pop() {
+      let i = match next {
+        Work::Visit(i) => i,
+        Work::Build(conclusion, rule, label, n) => {
+          let premises = done.split_off(done.len() - n);
+          done.push(Proof::new(conclusion, Step::Inference { rule, label, premises }));
+          continue;
+        }
+      };
+
+      let conclusion = formulas
+        .entry(i.logic)
+        .or_insert_with(|| Arc::new(arena.logic(i.logic)))
+        .clone();
+
+      let label = if Rc::weak_count(&i.marker) > 0 {
+        after += 1;
+        i.marker.replace(after);
+        Some(after)
+      } else {
+        None
+      };
+
+      match i.inference {
+        InferenceType::Axiom(ref marker) => {
+          let step = Step::Assumption(*marker.upgrade().unwrap().borrow());
+          done.push(Proof::new(conclusion, step));
+        }
+        InferenceType::Premise => done.push(Proof::new(conclusion, Step::Premise)),
+        InferenceType::Rule(rule, ref premises) => {
+          work.push(Work::Build(conclusion, rule, label, premises.len()));
+          work.extend(premises.iter().rev().map(Work::Visit));
+        }
       }
-      InferenceType::BinaryInf(ref i0, ref i1) => {
-        tree.push_str(&format!(
-          "{}\\infer{}{{{}}}{{\n",
-          indent,
-          marker,
-          self.logic.tex()
-        ));
-        i0.print_tex(tree, &format!("{}  ", indent), after);
-        tree.push_str(&format!("{}  &\n", indent));
-        i1.print_tex(tree, &format!("{}  ", indent), after);
-        tree.push_str(&format!("{}}}\n", indent));
+    }
+    done.pop().unwrap()
+  }
+
+  /// TeX記法（bussproofs.sty）で証明図を出力します．`prooftree`環境で囲まれます．
+  pub fn bussproofs(&self) -> String {
+    self.to_proof().bussproofs()
+  }
+
+  /// 簡略化した記法でFitch式の証明を出力します．
+  pub fn fitch(&self) -> String {
+    self.to_proof().fitch()
+  }
+
+  /// TeX記法（fitch.sty）でFitch式の証明を出力します．`nd`環境で囲まれます．
+  pub fn fitch_tex(&self) -> String {
+    self.to_proof().fitch_tex()
+  }
+
+  /// ヒルベルト流の公理系による導出に翻訳します．
+  /// 証明図が推論規則に従っていなければ，その誤りを[ProofError]として返します．
+  pub fn hilbert(&self) -> Result<Hilbert, ProofError> {
+    self.to_proof().hilbert()
+  }
+}
+
+impl Clone for Inference {
+  /// 複製した推論は新しいマーカーを持ち，複製した部分で閉じられる仮定はそのマーカーを指します．
+  /// 同じ推論の複製が証明図の中で入れ子になっても，仮定を閉じる推論を取り違えません．
+  fn clone(&self) -> Self {
+    let mut markers: HashMap<*const RefCell<usize>, Rc<RefCell<usize>>> = HashMap::new();
+    let mut stack = vec![self];
+    while let Some(i) = stack.pop() {
+      if Rc::weak_count(&i.marker) > 0 {
+        markers.insert(Rc::as_ptr(&i.marker), new_marker());
       }
-      InferenceType::TrinaryInf(ref i0, ref i1, ref i2) => {
-        tree.push_str(&format!(
-          "{}\\infer{}{{{}}}{{\n",
-          indent,
-          marker,
-          self.logic.tex()
-        ));
-        i0.print_tex(tree, &format!("{}  ", indent), after);
-        tree.push_str(&format!("{}  &\n", indent));
-        i1.print_tex(tree, &format!("{}  ", indent), after);
-        tree.push_str(&format!("{}  &\n", indent));
-        i2.print_tex(tree, &format!("{}  ", indent), after);
-        tree.push_str(&format!("{}}}\n", indent));
+      stack.extend(i.premises());
+    }
+
+    self.fold(|i, premises| Self {
+      logic: i.logic,
+      marker: markers.get(&Rc::as_ptr(&i.marker)).cloned().unwrap_or_else(new_marker),
+      inference: match i.inference {
+        InferenceType::Axiom(ref marker) => InferenceType::Axiom(match markers.get(&Weak::as_ptr(marker)) {
+          Some(marker) => Rc::downgrade(marker),
+          None => marker.clone(),
+        }),
+        InferenceType::Premise => InferenceType::Premise,
+        InferenceType::Rule(rule, _) => InferenceType::Rule(rule, premises),
+      },
+      arena: i.arena.clone(),
+    })
+  }
+}
+
+impl Drop for Inference {
+  /// 深い証明図でもスタックを溢れさせないよう，前提を明示的なスタックに移してから破棄します．
+  fn drop(&mut self) {
+    let mut stack = match self.inference {
+      InferenceType::Rule(_, ref mut premises) => std::mem::take(premises),
+      _ => return,
+    };
+    while let Some(mut i) = stack.pop() {
+      if let InferenceType::Rule(_, ref mut premises) = i.inference {
+        stack.append(premises);
       }
     }
   }
 }
 
-impl TeX for Inference<'_> {
+impl From<&Inference> for Proof {
+  fn from(inference: &Inference) -> Self {
+    inference.to_proof()
+  }
+}
+
+impl TeX for Inference {
   fn tex(&self) -> String {
-    let mut tree = String::new();
-    self.print_tex(&mut tree, "", &mut 0);
-    tree
+    self.to_proof().tex()
   }
 }
 
-impl Display for Inference<'_> {
+impl Display for Inference {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let mut tree = String::new();
-    self.print(&mut tree, "", &mut 0);
-    write!(f, "{}", tree)
+    write!(f, "{}", self.to_proof())
   }
 }
 
-type SolveResult<'a> = Result<Inference<'a>, SolveError>;
+type SolveResult = Result<Inference, SolveError>;
 
 /// 証明に失敗した場合のエラーです．
 #[derive(Debug)]
-pub struct SolveError {
+pub enum SolveError {
+  /// 探索を尽くしても証明できなかった場合です．探索は完全なので，
+  /// 直観主義論理（古典論理の規則を選んだ場合は古典論理）上証明不可能であることを意味します．
+  Unprovable(Stuck),
+
+  /// 資源の制限に達し，探索を打ち切った場合です．証明不可能であることは意味しません．
+  ResourceExhausted(Resource),
+}
+
+/// 探索が行き詰まった箇所です．
+/// 可逆な規則で問題を分解し尽くした後，可逆でない規則をすべて試みて失敗した問題を持ちます．
+#[derive(Debug)]
+pub struct Stuck {
+  /// 探索が行き詰まった問題の目的の論理式です．
   logic: Logic,
+
+  /// 探索が行き詰まった問題で用いることができた仮定です．推件の前提と，閉じられていない仮定を分解したものです．
+  assumptions: Vec<Logic>,
+
+  /// 探索が行き詰まった問題で試みて失敗した推論規則と，その対象の論理式です．
+  attempts: Vec<(Rule, Logic)>,
+
+  /// 直観主義論理で失敗した場合の，反例となるクリプキモデルです．
+  countermodel: Option<Kripke>,
+}
+
+impl Stuck {
+  /// 探索が行き詰まった，推論できなかった論理式です．
+  pub fn logic(&self) -> &Logic {
+    &self.logic
+  }
+
+  /// 探索が行き詰まった箇所で用いることができた仮定です．
+  pub fn assumptions(&self) -> &[Logic] {
+    &self.assumptions
+  }
+
+  /// 探索が行き詰まった箇所で試みて失敗した推論規則と，その対象の論理式です．
+  /// 論理和の導入では目的の論理式を，論理包含と否定の除去では用いた仮定を持ちます．
+  pub fn attempts(&self) -> &[(Rule, Logic)] {
+    &self.attempts
+  }
+
+  /// 直観主義論理で失敗した場合の，入力の推件の反例となるクリプキモデルです．
+  /// 根の世界0は前提をすべて強制し，結論を強制しません．古典論理で失敗した場合は[None]です．
+  pub fn countermodel(&self) -> Option<&Kripke> {
+    self.countermodel.as_ref()
+  }
 }
 
 impl Display for SolveError {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(f, "could not infer: {}", self.logic)
+    match self {
+      Self::Unprovable(stuck) => write!(f, "could not infer: {}", stuck.logic),
+      Self::ResourceExhausted(resource) => write!(f, "search {}", resource),
+    }
   }
 }
 
 impl Error for SolveError {}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+  use std::sync::atomic::AtomicBool;
+  use std::time::Duration;
+
+  /// 証明に失敗した結果から，探索が行き詰まった箇所を取り出します．
+  fn stuck(result: SolveResult) -> Stuck {
+    match result {
+      Err(SolveError::Unprovable(stuck)) => stuck,
+      _ => panic!("the input should be unprovable"),
+    }
+  }
+
+  #[test]
+  fn test_rule() {
+    let logic = Logic::new("A and B to B").unwrap();
+    let inference = logic.solve(None).unwrap();
+    assert_eq!(
+      inference.to_string(),
+      "A ∧ B → B [→I] : 1\n+ B [∧E]\n  + A ∧ B from: 1\n"
+    );
+    assert_eq!(
+      inference.tex(),
+      "\\infer[\\to I\\ 1]{A \\land B \\to B}{\n  \\infer[\\land E]{B}{\n    [A \\land B]_{1}\n  }\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_bussproofs() {
+    let logic = Logic::new("A and B to B").unwrap();
+    let inference = logic.solve(None).unwrap();
+    assert_eq!(
+      inference.bussproofs(),
+      [
+        "\\begin{prooftree}",
+        "      \\AxiomC{$[A \\land B]_{1}$}",
+        "    \\RightLabel{$\\land E$}",
+        "    \\UnaryInfC{$B$}",
+        "  \\RightLabel{$\\to I\\ 1$}",
+        "  \\UnaryInfC{$A \\land B \\to B$}",
+        "\\end{prooftree}",
+        "",
+      ]
+      .join("\n")
+    );
+  }
+
+  #[test]
+  fn test_premise() {
+    let sequent: Sequent = "A to B, A |- B".parse().unwrap();
+    let inference = sequent.solve(None).unwrap();
+    assert_eq!(inference.to_string(), "B [→E]\n+ A\n+ A → B\n");
+    assert_eq!(
+      inference.tex(),
+      "\\infer[\\to E]{B}{\n  A\n  &\n  A \\to B\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_classical() {
+    let logic = Logic::new("not not A to A").unwrap();
+    assert!(logic.solve(None).is_err());
+    for classical in [
+      Classical::ExcludedMiddle,
+      Classical::DoubleNegation,
+      Classical::Reductio,
+    ] {
+      assert!(logic.solve(Some(classical)).is_ok());
+    }
+  }
+
+  #[test]
+  fn test_normalize() {
+    let logic = Logic::new("(A or B) and (C and D) to D").unwrap();
+    let inference = logic.solve(None).unwrap();
+    let size = inference.size();
+    let normal = inference.normalize();
+    assert!(normal.size() < size);
+    assert_eq!(
+      normal.to_string(),
+      "(A ∨ B) ∧ (C ∧ D) → D [→I] : 1\n+ D [∧E]\n  + C ∧ D [∧E]\n    + (A ∨ B) ∧ (C ∧ D) from: 1\n"
+    );
+
+    // 正規化した証明図も，古典論理の規則を用いたものを含めて推論規則に従います．
+    for s in [
+      "(A or B) and (C and D) to D",
+      "((not C and A) iff (A or (A and B))) to (((C iff C) iff not B) to not B)",
+      "(B iff not B to B) or (B and (A iff A) iff (A iff A) to (C iff B))",
+      "(((C and B) iff C) or ((C iff A) iff (C and A))) or not (not A to (A iff B))",
+      "((B iff (C or B)) or (not A iff (B or C))) or (not (C iff C) iff ((A and A) and not C))",
+      "((C to (B iff B)) iff ((A to B) or not B)) or ((C iff (A to C)) iff ((C and C) and (A or B)))",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      for classical in [
+        None,
+        Some(Classical::ExcludedMiddle),
+        Some(Classical::DoubleNegation),
+        Some(Classical::Reductio),
+      ] {
+        if let Ok(inference) = logic.solve(classical) {
+          let normal = inference.normalize();
+          assert_eq!(normal.to_proof().check(&[], classical), Ok(()), "{} {:?}", s, classical);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_iff() {
+    // 同値の導入を複製して入れ子にしても，仮定はそれぞれの複製で閉じられます．
+    for s in [
+      "B to not (not B iff (B iff B))",
+      "((not C and A) iff (A or (A and B))) to (((C iff C) iff not B) to not B)",
+      "not ((A iff B) and B) or (((B iff C) iff not C) to (B to A))",
+      "(((C or C) or (A iff A)) iff not (A or A)) iff (((A iff B) iff not B) iff ((C and B) to C))",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      for classical in [None, Some(Classical::Reductio)] {
+        let inference = logic.solve(classical).unwrap();
+        assert_eq!(inference.to_proof().check(&[], classical), Ok(()), "{}", s);
+      }
+    }
+
+    for s in [
+      "(((B iff A) iff B) to ((C iff C) iff (C to A))) or B",
+      "(((C and B) iff C) or ((C iff A) iff (C and A))) or not (not A to (A iff B))",
+      "(((C iff C) iff (B and B)) iff ((C iff B) or B)) or not ((B and B) or C)",
+      "((B iff (C or B)) or (not A iff (B or C))) or (not (C iff C) iff ((A and A) and not C))",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      let inference = logic.solve(Some(Classical::Reductio)).unwrap();
+      assert_eq!(inference.to_proof().check(&[], Some(Classical::Reductio)), Ok(()), "{}", s);
+    }
+  }
+
+  #[test]
+  fn test_complete() {
+    for s in [
+      "not not (A or not A)",
+      "not not (not not A to A)",
+      "((((A to B) to A) to A) to B) to B",
+      "not not not A to not A",
+      "(A iff B) to (B iff A)",
+      "((A iff B) to C) to (A to B) to (B to A) to C",
+      "(A or B) and C to A and C or B and C",
+      "(A to B) to not B to not A",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      assert!(logic.solve(None).is_ok(), "{}", s);
+    }
+
+    for s in [
+      "A or not A",
+      "not not A to A",
+      "((A to B) to A) to A",
+      "not (A and B) to not A or not B",
+      "(A to B or C) to (A to B) or (A to C)",
+    ] {
+      let logic = Logic::new(s).unwrap();
+      assert!(logic.check_all().is_ok());
+      let err = stuck(logic.solve(None));
+      let model = err.countermodel().unwrap();
+      assert!(!model.forces(0, &logic), "{}", s);
+    }
+  }
+
+  #[test]
+  fn test_error() {
+    let logic = Logic::new("not not A to A").unwrap();
+    let err = stuck(logic.solve(None));
+    let not_not = Logic::new("not not A").unwrap();
+    assert_eq!(err.logic(), &Logic::new("A").unwrap());
+    assert_eq!(err.assumptions(), std::slice::from_ref(&not_not));
+    assert_eq!(err.attempts(), &[(Rule::NotElim, not_not)]);
+
+    let sequent: Sequent = "A to B or C |- (A to B) or (A to C)".parse().unwrap();
+    let err = stuck(sequent.solve(None));
+    assert_eq!(err.logic(), &sequent.conclusion);
+    assert_eq!(err.attempts(), &[(Rule::OrIntro, sequent.conclusion.clone())]);
+  }
+
+  #[test]
+  fn test_limits() {
+    let logic = Logic::new("(A iff B) and (B iff C) to (A iff C)").unwrap();
+    let exhausted = |limits| match logic.solve_with(None, limits) {
+      Err(SolveError::ResourceExhausted(resource)) => Some(resource),
+      _ => None,
+    };
+
+    let limits = Limits {
+      max_depth: Some(3),
+      ..Limits::default()
+    };
+    assert_eq!(exhausted(limits), Some(Resource::Depth(3)));
+
+    let limits = Limits {
+      max_nodes: Some(5),
+      ..Limits::default()
+    };
+    assert_eq!(exhausted(limits), Some(Resource::Nodes(5)));
+
+    let limits = Limits {
+      timeout: Some(Duration::ZERO),
+      ..Limits::default()
+    };
+    assert_eq!(exhausted(limits), Some(Resource::Timeout(Duration::ZERO)));
+
+    let limits = Limits {
+      cancel: Some(Arc::new(AtomicBool::new(true))),
+      ..Limits::default()
+    };
+    assert_eq!(exhausted(limits), Some(Resource::Cancelled));
+
+    let limits = Limits {
+      max_depth: Some(100),
+      max_nodes: Some(10000),
+      timeout: Some(Duration::from_secs(60)),
+      cancel: Some(Arc::new(AtomicBool::new(false))),
+    };
+    assert!(logic.solve_with(None, limits).is_ok());
+
+    // 制限に達した場合は，他の規則を試みずに打ち切ります．
+    let logic = Logic::new("((A to B) to A) to A").unwrap();
+    let limits = Limits {
+      max_depth: Some(2),
+      ..Limits::default()
+    };
+    assert!(matches!(
+      logic.solve_with(None, limits),
+      Err(SolveError::ResourceExhausted(Resource::Depth(2)))
+    ));
+  }
+
+  #[test]
+  fn test_deep() {
+    // 深く入れ子になった論理式でも，探索，正規化，出力でスタックを溢れさせません．
+    const DEPTH: usize = 10000;
+    let logic = Logic::new(&vec!["A"; DEPTH + 1].join(" to ")).unwrap();
+    let inference = logic.solve(None).unwrap();
+    assert_eq!(inference.size(), DEPTH + 1);
+    assert_eq!(inference.clone().normalize().size(), DEPTH + 1);
+
+    let not = format!("{}A", "not ".repeat(DEPTH));
+    let logic = Logic::new(&format!("({}) to {}", not, not)).unwrap();
+    assert!(logic.check_all().is_ok());
+    let inference = logic.solve(Some(Classical::Reductio)).unwrap();
+    let not = Logic::new(&not).unwrap();
+    assert_eq!(
+      inference.to_string(),
+      format!("{} [→I] : 1\n+ {} from: 1\n", logic, not)
+    );
+    assert_eq!(
+      inference.tex(),
+      format!("\\infer[\\to I\\ 1]{{{}}}{{\n  [{}]_{{1}}\n}}\n", logic.tex(), not.tex())
+    );
+
+    // すべての出力形式と体系，および検査，反例，真理値表の出力でも，スタックを溢れさせません．
+    use crate::exec::*;
+    let input = logic.to_string();
+    for system in [System::NaturalDeduction, System::SequentCalculus, System::Hilbert] {
+      for format in [Format::Plain, Format::Proof, Format::Bussproofs, Format::Fitch, Format::FitchTex] {
+        let options = ExecOptions {
+          format,
+          classical: Some(Classical::Reductio),
+          normalize: format == Format::Plain && system != System::SequentCalculus,
+          system,
+          ..ExecOptions::default()
+        };
+        assert!(exec(&input, &options).is_ok(), "{:?} {:?}", system, format);
+      }
+    }
+    let proof = exec(&input, &ExecOptions::default()).unwrap();
+    assert!(exec_check(&input, &proof, None).is_ok());
+    assert!(matches!(
+      exec_countermodel(&input, ModelFormat::Plain, Limits::default()),
+      Err(ExecError::Provable(_))
+    ));
+
+    let and = format!("{} to B", vec!["A"; DEPTH + 1].join(" and "));
+    let model = exec_countermodel(&and, ModelFormat::Plain, Limits::default()).unwrap();
+    assert_eq!(model, "worlds: w0\nw0 forces A\n");
+
+    // 真理値表はすべての部分式を見出しに持ち，出力が深さの2乗に比例するため，浅くした論理式で確かめます．
+    let not = format!("{}A", "not ".repeat(DEPTH / 10));
+    let table = exec_table(&format!("({}) to {}", not, not), TableFormat::Csv).unwrap();
+    assert_eq!(table.lines().count(), 3);
+  }
+
+  #[test]
+  fn test_walk() {
+    let logic = Logic::new("A and B to B").unwrap();
+    let inference = logic.solve(None).unwrap();
+    assert_eq!(inference.conclusion(), logic);
+    assert_eq!(inference.rule(), Some(Rule::ToIntro));
+    assert_eq!(inference.discharged(), vec![Logic::new("A and B").unwrap()]);
+
+    let elim = inference.premises()[0];
+    assert_eq!(elim.rule(), Some(Rule::AndElim));
+    assert!(elim.discharged().is_empty());
+
+    let leaf = elim.premises()[0];
+    assert!(leaf.is_assumption());
+    assert!(leaf.premises().is_empty());
+    assert!(inference.discharges(leaf));
+    assert!(!elim.discharges(leaf));
+  }
+
+  #[test]
+  fn test_arena() {
+    let arena = Rc::new(RefCell::new(Arena::new()));
+    let (a, b) = {
+      let mut arena = arena.borrow_mut();
+      let a = arena.insert(&Logic::new("A and B to B").unwrap());
+      let b = arena.insert(&Logic::new("A and B to A").unwrap());
+      (a, b)
+    };
+    assert_eq!(arena.borrow().len(), 5);
+
+    let i0 = Problem::in_arena(arena.clone(), a, &[], None).solve().unwrap();
+    let i1 = Problem::in_arena(arena.clone(), b, &[], None).solve().unwrap();
+    assert_eq!(i0.formula(), a);
+    assert!(Rc::ptr_eq(i0.arena(), i1.arena()));
+    assert_eq!(i1.conclusion(), Logic::new("A and B to A").unwrap());
+    assert_eq!(i0.discharged(), i1.discharged());
+  }
+}
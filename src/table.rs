@@ -0,0 +1,288 @@
+//! 論理式の真理値表を生成し，出力するモジュールです．
+//! 原子式の真偽値のすべての組み合わせについて，論理式とそのすべての部分式を評価します．
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use super::{arena::*, logic::*, TeX};
+
+/// 真理値表を示す構造です．
+#[derive(Debug, Clone)]
+pub struct TruthTable {
+  /// 原子式の名前です．名前順に並んでいます．
+  bases: Vec<String>,
+
+  /// 原子式以外の部分式です．部分式が先に，それを含む式が後に並び，最後が元の論理式です．
+  columns: Vec<Logic>,
+
+  /// 各行の真偽値です．原子式の真偽値，部分式の真偽値の順に並んでいます．
+  rows: Vec<Vec<bool>>,
+}
+
+/// 真理値表の出力形式を示す列挙子です．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+  /// 等幅フォントで揃えた表です．
+  Plain,
+
+  /// Markdownの表です．
+  Markdown,
+
+  /// CSVです．
+  Csv,
+
+  /// TeXの`tabular`環境です．
+  Tex,
+}
+
+impl FromStr for TableFormat {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "plain" => Ok(Self::Plain),
+      "markdown" => Ok(Self::Markdown),
+      "csv" => Ok(Self::Csv),
+      "tex" => Ok(Self::Tex),
+      _ => Err(format!(
+        "unknown table format: {} (expected plain, markdown, csv or tex)",
+        s
+      )),
+    }
+  }
+}
+
+/// 論理式`id`の部分式を，部分式が先になるように重複なく列挙します．
+fn subformulas(arena: &Arena, id: FormulaId) -> Vec<FormulaId> {
+  let mut list = vec![];
+  let mut listed = HashSet::new();
+  let mut stack = vec![(id, false)];
+  while let Some((id, visited)) = stack.pop() {
+    if listed.contains(&id) {
+      continue;
+    }
+    if visited {
+      listed.insert(id);
+      list.push(id);
+    } else {
+      stack.push((id, true));
+      stack.extend(arena[id].children().into_iter().rev().map(|child| (child, false)));
+    }
+  }
+  list
+}
+
+/// 真偽値を表に書く文字にします．
+fn show_bool(b: bool) -> &'static str {
+  if b {
+    "T"
+  } else {
+    "F"
+  }
+}
+
+impl TruthTable {
+  /// 論理式の真理値表を生成します．行は，すべての原子式が真である行から始まります．
+  /// 原子式が`usize::BITS`個以上あれば，行を列挙できないので[CheckError::TooManyBases]を返します．
+  pub fn new(logic: &Logic) -> Result<Self, CheckError> {
+    let mut arena = Arena::new();
+    let id = arena.insert(logic);
+    let list = subformulas(&arena, id);
+
+    let mut bases: Vec<(String, FormulaId)> = list
+      .iter()
+      .filter_map(|&id| match arena[id] {
+        Formula::Base(ref name) => Some((name.clone(), id)),
+        _ => None,
+      })
+      .collect();
+    bases.sort();
+
+    let list: Vec<FormulaId> = list
+      .into_iter()
+      .filter(|&id| !matches!(arena[id], Formula::Base(_)))
+      .collect();
+    let columns = list.iter().map(|&id| arena.logic(id)).collect();
+
+    // 部分式は先に並んでいるので，各行で列を順に評価すれば，部分式の値は既に求まっています．
+    let n = bases.len();
+    if n >= usize::BITS as usize {
+      return Err(CheckError::TooManyBases(n));
+    }
+    let rows = (0..1usize << n)
+      .map(|i| {
+        let mut row = vec![];
+        let mut values = HashMap::new();
+        for (j, &(_, id)) in bases.iter().enumerate() {
+          let value = i >> (n - 1 - j) & 1 == 0;
+          values.insert(id, value);
+          row.push(value);
+        }
+        for &id in &list {
+          let value = |id| values[&id];
+          let value = match arena[id] {
+            Formula::Base(_) => unreachable!(),
+            Formula::Cont => false,
+            Formula::Not(logic) => !value(logic),
+            Formula::And(left, right) => value(left) && value(right),
+            Formula::Or(left, right) => value(left) || value(right),
+            Formula::To(left, right) => !value(left) || value(right),
+            Formula::Iff(left, right) => value(left) == value(right),
+          };
+          values.insert(id, value);
+          row.push(value);
+        }
+        row
+      })
+      .collect();
+    let bases = bases.into_iter().map(|(name, _)| name).collect();
+
+    Ok(Self {
+      bases,
+      columns,
+      rows,
+    })
+  }
+
+  /// 表の見出しを，原子式，部分式の順に列挙します．
+  fn headers(&self, tex: bool) -> Vec<String> {
+    let show = |logic: &Logic| if tex { logic.tex() } else { logic.to_string() };
+    self
+      .bases
+      .iter()
+      .map(|base| show(&Logic::Base(base.clone())))
+      .chain(self.columns.iter().map(show))
+      .collect()
+  }
+
+  /// 指定された形式で真理値表を出力します．
+  pub fn print(&self, format: TableFormat) -> String {
+    match format {
+      TableFormat::Plain => self.plain(),
+      TableFormat::Markdown => self.markdown(),
+      TableFormat::Csv => self.csv(),
+      TableFormat::Tex => self.tex(),
+    }
+  }
+
+  /// 等幅フォントで揃えた表を出力します．
+  pub fn plain(&self) -> String {
+    let headers = self.headers(false);
+    let widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+
+    let line = |cells: Vec<&str>| {
+      let cells: Vec<String> = cells
+        .iter()
+        .zip(&widths)
+        .map(|(cell, &width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+        .collect();
+      format!("{}\n", cells.join(" | ").trim_end())
+    };
+
+    let mut table = line(headers.iter().map(|h| h.as_str()).collect());
+    let rules: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    table.push_str(&format!("{}\n", rules.join("-+-")));
+    for row in &self.rows {
+      table.push_str(&line(row.iter().map(|&b| show_bool(b)).collect()));
+    }
+    table
+  }
+
+  /// Markdownの表を出力します．
+  pub fn markdown(&self) -> String {
+    let headers = self.headers(false);
+    let mut table = format!("| {} |\n", headers.join(" | "));
+    table.push_str(&format!("|{}\n", ":-:|".repeat(headers.len())));
+    for row in &self.rows {
+      let cells: Vec<&str> = row.iter().map(|&b| show_bool(b)).collect();
+      table.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    table
+  }
+
+  /// CSVを出力します．見出しは二重引用符で囲みます．
+  pub fn csv(&self) -> String {
+    let headers: Vec<String> = self
+      .headers(false)
+      .iter()
+      .map(|h| format!("\"{}\"", h.replace('"', "\"\"")))
+      .collect();
+    let mut table = format!("{}\n", headers.join(","));
+    for row in &self.rows {
+      let cells: Vec<&str> = row.iter().map(|&b| show_bool(b)).collect();
+      table.push_str(&format!("{}\n", cells.join(",")));
+    }
+    table
+  }
+}
+
+impl TeX for TruthTable {
+  /// TeXの`tabular`環境を出力します．原子式と部分式の間に縦線を引きます．
+  fn tex(&self) -> String {
+    let headers: Vec<String> = self.headers(true).iter().map(|h| format!("${}$", h)).collect();
+    let mut table = format!(
+      "\\begin{{tabular}}{{{}|{}}}\n",
+      "c".repeat(self.bases.len()),
+      "c".repeat(self.columns.len())
+    );
+    table.push_str(&format!("  {} \\\\\n  \\hline\n", headers.join(" & ")));
+    for row in &self.rows {
+      let cells: Vec<&str> = row.iter().map(|&b| show_bool(b)).collect();
+      table.push_str(&format!("  {} \\\\\n", cells.join(" & ")));
+    }
+    table.push_str("\\end{tabular}\n");
+    table
+  }
+}
+
+#[cfg(test)]
+mod test {
+  //! テストを行うサブモジュールです．
+
+  use super::*;
+
+  #[test]
+  fn test_plain() {
+    let table = TruthTable::new(&Logic::new("A to not B").unwrap()).unwrap();
+    assert_eq!(
+      table.plain(),
+      [
+        "A | B | ¬ B | A → ¬ B",
+        "--+---+-----+--------",
+        "T | T | F   | F",
+        "T | F | T   | T",
+        "F | T | F   | T",
+        "F | F | T   | T",
+        "",
+      ]
+      .join("\n")
+    );
+  }
+
+  #[test]
+  fn test_tex() {
+    let table = TruthTable::new(&Logic::new("A or A").unwrap()).unwrap();
+    assert_eq!(
+      table.tex(),
+      [
+        "\\begin{tabular}{c|c}",
+        "  $A$ & $A \\lor A$ \\\\",
+        "  \\hline",
+        "  T & T \\\\",
+        "  F & F \\\\",
+        "\\end{tabular}",
+        "",
+      ]
+      .join("\n")
+    );
+  }
+
+  #[test]
+  fn test_too_many_bases() {
+    let bases: Vec<String> = (0..64).map(|i| format!("P{}", i)).collect();
+    let logic = Logic::new(&bases.join(" or ")).unwrap();
+    assert!(matches!(
+      TruthTable::new(&logic),
+      Err(CheckError::TooManyBases(64))
+    ));
+  }
+}